@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use mpl_bubblegum::programs::SPL_ACCOUNT_COMPRESSION_ID;
+
+// Raw spl account compression instructions, for trees owned directly by this program
+// rather than through bubblegum. Discriminators are sha256("global:<name>")[..8]
+
+const INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR: [u8; 8] = [191, 11, 119, 7, 180, 107, 220, 110];
+const APPEND_DISCRIMINATOR: [u8; 8] = [149, 120, 18, 222, 236, 225, 88, 203];
+
+pub fn init_empty_merkle_tree_ix(
+    tree: Pubkey,
+    authority: Pubkey,
+    noop: Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Instruction {
+    let mut data = INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&max_depth.to_le_bytes());
+    data.extend_from_slice(&max_buffer_size.to_le_bytes());
+
+    Instruction {
+        program_id: SPL_ACCOUNT_COMPRESSION_ID,
+        accounts: vec![
+            AccountMeta::new(tree, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(noop, false),
+        ],
+        data,
+    }
+}
+
+pub fn append_ix(tree: Pubkey, authority: Pubkey, noop: Pubkey, leaf: [u8; 32]) -> Instruction {
+    let mut data = APPEND_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&leaf);
+
+    Instruction {
+        program_id: SPL_ACCOUNT_COMPRESSION_ID,
+        accounts: vec![
+            AccountMeta::new(tree, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(noop, false),
+        ],
+        data,
+    }
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::hash::hash;
+
+    #[test]
+    fn test_discriminators() {
+        assert_eq!(hash(b"global:init_empty_merkle_tree").to_bytes()[..8], INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR);
+        assert_eq!(hash(b"global:append").to_bytes()[..8], APPEND_DISCRIMINATOR);
+    }
+
+    #[test]
+    fn test_append_ix() {
+        let tree = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let ix = append_ix(tree, authority, Pubkey::new_unique(), [7; 32]);
+
+        assert_eq!(ix.program_id, SPL_ACCOUNT_COMPRESSION_ID);
+        assert_eq!(&ix.data[..8], &APPEND_DISCRIMINATOR);
+        assert_eq!(&ix.data[8..], &[7; 32]);
+        assert!(ix.accounts[0].is_writable);
+        assert!(ix.accounts[1].is_signer);
+        assert_eq!(ix.accounts[1].pubkey, authority);
+    }
+}
@@ -27,5 +27,69 @@ pub enum Errors {
     #[msg("This model has already submitted a prediction for this task")]
     DuplicatePrediction,
     #[msg("Total reputation of models must be greater than zero")]
-    ZeroTotalReputation
+    ZeroTotalReputation,
+    #[msg("Metadata uri must not be empty")]
+    EmptyUri,
+    #[msg("Metadata uri must be at most 200 bytes")]
+    UriTooLong,
+    #[msg("Metadata name must be at most 32 bytes")]
+    NameTooLong,
+    #[msg("Signer is not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Unsupported max depth and max buffer size combination for tree")]
+    InvalidTreeConfig,
+    #[msg("Tree has no remaining capacity for new leaves")]
+    TreeFull,
+    #[msg("Tree account is too small for the requested max depth and max buffer size")]
+    TreeAccountTooSmall,
+    #[msg("Program state cannot track any more trees")]
+    TooManyTrees,
+    #[msg("Batch must contain between 1 and 8 items")]
+    InvalidBatchSize,
+    #[msg("Signer is not listed in the leaf's creators")]
+    CreatorNotFound,
+    #[msg("Leaf metadata is immutable")]
+    ImmutableMetadata,
+    #[msg("Number of proof accounts does not match the tree depth minus its canopy depth")]
+    InvalidProofLength,
+    #[msg("Canopy depth must not exceed the tree's max depth")]
+    InvalidCanopyDepth,
+    #[msg("Tree still has active leaves")]
+    TreeNotEmpty,
+    #[msg("Mint allowlist cannot hold more than 16 minters")]
+    TooManyMinters,
+    #[msg("Minter is not on the mint allowlist")]
+    MinterNotFound,
+    #[msg("Leaf is frozen and cannot be transferred or burned")]
+    LeafFrozen,
+    #[msg("Tree config is already initialized by another creator")]
+    AlreadyInitialized,
+    #[msg("Leaf counter overflowed")]
+    CounterOverflow,
+    #[msg("Leaf counter cannot go below zero")]
+    CounterUnderflow,
+    #[msg("Creator shares must sum to 100")]
+    InvalidCreatorShares,
+    #[msg("Seller fee must be at most 10000 basis points")]
+    InvalidSellerFee,
+    #[msg("Authority seeds are empty, too long, or do not derive a valid pda")]
+    InvalidAuthoritySeeds,
+    #[msg("No uri is registered for the content hash")]
+    ContentNotRegistered,
+    #[msg("Root is older than the allowed number of tree updates, refetch the proof")]
+    RootTooStale,
+    #[msg("Leaf is not in the expected verified collection")]
+    NotInCollection,
+    #[msg("Tree root after the update does not match the expected root")]
+    RootMismatch,
+    #[msg("At most 5 creators are allowed")]
+    TooManyCreators,
+    #[msg("Tree must be owned by spl account compression and match the tree config")]
+    InvalidTreeAccount,
+    #[msg("Program is paused")]
+    Paused,
+    #[msg("Tree account size does not match the max depth, max buffer size and canopy depth")]
+    TreeAccountSizeMismatch,
+    #[msg("Transfer batch needs more proof accounts than a transaction can lock")]
+    TransferBatchTooLarge
 }
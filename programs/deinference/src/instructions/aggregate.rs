@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::{error::Errors, state::{InferenceRequest, ModelData, ResultEntry, TaskData}};
+use crate::{error::Errors, state::{Config, InferenceRequest, ModelData, ResultEntry, TaskData}};
 
 #[derive(Accounts)]
 #[instruction(request_id: u16)]
@@ -18,25 +18,29 @@ pub struct Aggregate<'info> {
     )]
     pub task_data: Account<'info, TaskData>,
 
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
 }
 
 pub fn aggregate(ctx: Context<Aggregate>, _request_id: u16, algorithm: AggregationAlgorithm) -> Result<()> {
+    verbose_msg!("aggregate: request_state {} bump {}", ctx.accounts.request_state.key(), ctx.bumps.request_state);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
     let predictions = &ctx.accounts.request_state.results;
     let models = &ctx.accounts.task_data.models;
     require!(predictions.len() >= ctx.accounts.request_state.required_predictions.into(), Errors::NotEnoughPredictions);
     match algorithm {
         AggregationAlgorithm::WeightedMedian => {
-            let aggregate_pred = weighted_median(predictions, models);
+            let _aggregate_pred = weighted_median(predictions, models);
         },
     }
     Ok(())
 }
 
 // Prediction must be exactly 4 bytes
-fn weighted_median(predictions: &Vec<ResultEntry>, models: &Vec<ModelData>) -> Option<f32> {
+fn weighted_median(predictions: &[ResultEntry], models: &[ModelData]) -> Option<f32> {
     let mut total_reputation: u32 = 0;
 
-    let mut prediction_reputation_map: Vec<(f32, u32)> = predictions
+    let prediction_reputation_map: Vec<(f32, u32)> = predictions
         .iter()
         .filter_map(|result| {
             models
@@ -80,7 +84,7 @@ fn weighted_median(predictions: &Vec<ResultEntry>, models: &Vec<ModelData>) -> O
 
     let mut weighted_predictions: Vec<f32> = normalized_prediction_reputation_map
     .iter()
-    .map(|(prediction, normalized_reputation)| prediction * *normalized_reputation as f32)
+    .map(|(prediction, normalized_reputation)| prediction * *normalized_reputation)
     .collect();
 
     weighted_predictions.sort_by(|a, b| {
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use crate::{compression::append_ix, error::Errors, merkle_tree::current_root, state::{Config, TreeRegistry, TREE_OWNER_SEED}, Noop, SplAccountCompression};
+
+#[event]
+pub struct LeafAppended {
+    pub tree: Pubkey,
+    pub leaf: [u8; 32],
+}
+
+#[derive(Accounts)]
+pub struct AppendLeaf<'info> {
+    // Wallet that created the tree with create_leaf_tree, the only one allowed to append
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", tree.key().as_ref()],
+        bump,
+        has_one = creator @ Errors::Unauthorized
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut)]
+    pub tree: UncheckedAccount<'info>,
+
+    // Only trees created with create_leaf_tree have this pda as their authority
+    #[account(
+        seeds = [TREE_OWNER_SEED, tree.key().as_ref()],
+        bump
+    )]
+    /// CHECK: This account used as a signing PDA only
+    pub tree_owner: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+}
+
+pub fn append_leaf(ctx: Context<AppendLeaf>, leaf: [u8; 32]) -> Result<()> {
+    verbose_msg!("append_leaf: tree_owner {} bump {}, tree {}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    append(ctx.accounts, ctx.bumps.tree_owner, leaf)
+}
+
+// Appends leaf and fails if the tree's new root is not expected_new_root, rolling the
+// append back. Lets a client that computed the root offchain detect a concurrent append
+pub fn append_and_check(ctx: Context<AppendLeaf>, leaf: [u8; 32], expected_new_root: [u8; 32]) -> Result<()> {
+    verbose_msg!("append_and_check: tree_owner {} bump {}, tree {}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    append(ctx.accounts, ctx.bumps.tree_owner, leaf)?;
+
+    let new_root = current_root(&ctx.accounts.tree.try_borrow_data()?).ok_or(Errors::TreeAccountTooSmall)?;
+    require!(new_root == expected_new_root, Errors::RootMismatch);
+
+    Ok(())
+}
+
+fn append(accounts: &mut AppendLeaf, tree_owner_bump: u8, leaf: [u8; 32]) -> Result<()> {
+    accounts.tree_registry.ensure_capacity(1)?;
+
+    let ix = append_ix(
+        accounts.tree.key(),
+        accounts.tree_owner.key(),
+        accounts.log_wrapper.key(),
+        leaf,
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            accounts.tree.to_account_info(),
+            accounts.tree_owner.to_account_info(),
+            accounts.log_wrapper.to_account_info(),
+        ],
+        &[&[
+            TREE_OWNER_SEED,
+            accounts.tree.key().as_ref(),
+            &[tree_owner_bump],
+        ]],
+    )?;
+    accounts.tree_registry.record_mints(1)?;
+
+    emit!(LeafAppended {
+        tree: accounts.tree.key(),
+        leaf,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::accounts::TreeConfig;
+use crate::{error::Errors, merkle_tree::{appended_leaf_count, canopy_depth, tree_dimensions}, state::{Config, MintAuthority, ProgramState, TreeInfo, TreeRegistry, MAX_TREES, TREE_OWNER_SEED}};
+
+#[derive(Accounts)]
+pub struct BackfillRegistry<'info> {
+    // Program authority, only it may upgrade legacy trees
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"knowledge"],
+        bump,
+        has_one = creator @ Errors::Unauthorized
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    // Init fails if the tree already has a registry
+    // space = account disc (8) + creator (32) + max_depth (4) + max_buffer_size (4) + canopy_depth (4) + created_at (8) + num_minted (8) + collection_minted (8) + last_nonce (8) + num_appended (8)
+    #[account(
+        init, payer = creator, space = 92, seeds = [b"registry", tree.key().as_ref()], bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    // space = account disc (8) + vec size (4) + max_#_minters (16) * pubkey (32)
+    #[account(
+        init, payer = creator, space = 524, seeds = [b"mint_auth", tree.key().as_ref()], bump
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    /// CHECK: Deserialized and checked against the tree_owner pda in the handler
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Only its header and rightmost path are read
+    pub tree: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [TREE_OWNER_SEED, tree.key().as_ref()],
+        bump
+    )]
+    /// CHECK: This account used as a signing PDA only
+    pub tree_owner: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Registers a tree created by this program before the registry existed, with the program
+// authority as its only minter
+pub fn backfill_registry(
+    ctx: Context<BackfillRegistry>,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Result<()> {
+    verbose_msg!("backfill_registry: tree_registry {} bump {}, tree_owner {} bump {}, tree {}", ctx.accounts.tree_registry.key(), ctx.bumps.tree_registry, ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let tree_config = TreeConfig::try_from(&ctx.accounts.tree_config.to_account_info())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    require_keys_eq!(tree_config.tree_creator, ctx.accounts.tree_owner.key(), Errors::Unauthorized);
+
+    let tree_data = ctx.accounts.tree.try_borrow_data()?;
+    let dimensions = tree_dimensions(&tree_data).ok_or(Errors::TreeAccountTooSmall)?;
+    require!(dimensions == (max_depth, max_buffer_size), Errors::InvalidTreeConfig);
+    let canopy_depth = canopy_depth(tree_data.len(), max_depth, max_buffer_size)
+        .ok_or(Errors::TreeAccountTooSmall)?;
+    let appended = appended_leaf_count(&tree_data).ok_or(Errors::TreeAccountTooSmall)?;
+
+    // Trees old enough to predate program_state are registered there too
+    let program_state = &mut ctx.accounts.program_state;
+    if program_state.get_tree(*ctx.accounts.tree.key).is_none() {
+        require!(program_state.trees.len() < MAX_TREES, Errors::TooManyTrees);
+        program_state.trees.push(TreeInfo {
+            tree_address: *ctx.accounts.tree.key,
+            tree_config: *ctx.accounts.tree_config.key,
+            current_index: u16::try_from(appended).map_err(|_| Errors::CounterOverflow)?
+        });
+        program_state.tree_count += 1;
+    }
+
+    let tree_registry = &mut ctx.accounts.tree_registry;
+    tree_registry.creator = *ctx.accounts.creator.key;
+    tree_registry.max_depth = max_depth;
+    tree_registry.max_buffer_size = max_buffer_size;
+    tree_registry.canopy_depth = canopy_depth;
+    tree_registry.created_at = Clock::get()?.unix_timestamp;
+    tree_registry.num_minted = appended as u64;
+    tree_registry.num_appended = appended as u64;
+
+    ctx.accounts.mint_authority.minters = vec![*ctx.accounts.creator.key];
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::BurnCpiBuilder;
+use crate::{error::Errors, state::{Config, TreeRegistry}, MplBubblegum, Noop, SplAccountCompression};
+use crate::instructions::require_not_frozen;
+use crate::proof::{is_bubblegum_tree, proof_accounts, require_proof_length};
+
+#[event]
+pub struct LeafBurned {
+    pub tree: Pubkey,
+    pub index: u32,
+    pub nonce: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(root: [u8; 32], data_hash: [u8; 32], creator_hash: [u8; 32], nonce: u64)]
+pub struct BurnKnowledge<'info> {
+    /// CHECK: This account is checked in the downstream instruction
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Must sign unless the leaf delegate signs instead
+    pub leaf_owner: UncheckedAccount<'info>,
+
+    /// CHECK: Must sign unless the leaf owner signs instead
+    pub leaf_delegate: UncheckedAccount<'info>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut, constraint = is_bubblegum_tree(&tree, tree_config.key) @ Errors::InvalidTreeAccount)]
+    pub tree: UncheckedAccount<'info>,
+
+    /// CHECK: Only checked to be empty, a leaf is frozen while this account exists
+    #[account(
+        seeds = [b"frozen", tree.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub frozen_marker: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", tree.key().as_ref()],
+        bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+// Burning an unknown or already burned leaf fails the proof check in the compression program,
+// that error is returned as is
+pub fn burn_knowledge<'info>(
+    ctx: Context<'_, '_, '_, 'info, BurnKnowledge<'info>>,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+) -> Result<()> {
+    verbose_msg!("burn_knowledge: frozen_marker {} bump {}, tree {}, nonce {}, index {}", ctx.accounts.frozen_marker.key(), ctx.bumps.frozen_marker, ctx.accounts.tree.key(), nonce, index);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let leaf_owner = &ctx.accounts.leaf_owner;
+    let leaf_delegate = &ctx.accounts.leaf_delegate;
+
+    require!(leaf_owner.is_signer || leaf_delegate.is_signer, Errors::Unauthorized);
+
+    require_not_frozen(&ctx.accounts.frozen_marker)?;
+    require_proof_length(&ctx.accounts.tree, ctx.remaining_accounts)?;
+    let proof = proof_accounts(ctx.remaining_accounts);
+
+    BurnCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .leaf_owner(&leaf_owner.to_account_info(), leaf_owner.is_signer)
+        .leaf_delegate(&leaf_delegate.to_account_info(), leaf_delegate.is_signer)
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
+        .compression_program(&ctx.accounts.compression_program.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .root(root)
+        .data_hash(data_hash)
+        .creator_hash(creator_hash)
+        .nonce(nonce)
+        .index(index)
+        .add_remaining_accounts(&proof)
+        .invoke()?;
+
+    ctx.accounts.tree_registry.record_removal()?;
+
+    emit!(LeafBurned {
+        tree: *ctx.accounts.tree.key,
+        index,
+        nonce,
+    });
+
+    Ok(())
+}
@@ -10,6 +10,7 @@ pub struct CloseAccount<'info> {
 }
 
 pub fn close_account(ctx: Context<CloseAccount>) -> Result<()> {
+    verbose_msg!("close_account: pda_account {}, receiver {}", ctx.accounts.pda_account.key(), ctx.accounts.receiver.key());
     // Transfer lamports from the PDA account to the receiver
     let pda_account_info = &ctx.accounts.pda_account;
     let lamports = **pda_account_info.lamports.borrow();
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::accounts::TreeConfig;
+use crate::{error::Errors, state::{is_valid_role, Config, MintAuthority, ProgramState, TreeRegistry, TREE_OWNER_SEED}};
+
+#[event]
+pub struct TreeClosed {
+    pub tree: Pubkey,
+    pub destination: Pubkey,
+}
+
+#[derive(Accounts)]
+#[instruction(role: Vec<u8>)]
+pub struct CloseTree<'info> {
+    // Wallet that created the tree through this program
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", tree.key().as_ref()],
+        bump,
+        has_one = creator @ Errors::Unauthorized,
+        close = destination
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"mint_auth", tree.key().as_ref()],
+        bump,
+        close = destination
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"knowledge"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// CHECK: Deserialized and checked against the tree_owner pda in the handler
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    #[account(constraint = is_valid_role(&role) @ Errors::InvalidAuthoritySeeds)]
+    pub tree: UncheckedAccount<'info>,
+
+    // Must be the authority for the role the tree was created with
+    #[account(
+        seeds = [TREE_OWNER_SEED, role.as_ref(), tree.key().as_ref()],
+        bump
+    )]
+    /// CHECK: This account used as a signing PDA only
+    pub tree_owner: UncheckedAccount<'info>,
+
+    /// CHECK: Only receives the reclaimed lamports
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+}
+
+// The tree and tree_config accounts are owned by the compression and bubblegum programs,
+// so only the accounts owned by this program are closed
+pub fn close_tree(ctx: Context<CloseTree>, role: Vec<u8>) -> Result<()> {
+    verbose_msg!("close_tree: tree_owner {} bump {}, tree_registry {} bump {}, tree {}, role {:?}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree_registry.key(), ctx.bumps.tree_registry, ctx.accounts.tree.key(), role);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let tree_config = TreeConfig::try_from(&ctx.accounts.tree_config.to_account_info())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    require_keys_eq!(tree_config.tree_creator, ctx.accounts.tree_owner.key(), Errors::Unauthorized);
+    require!(ctx.accounts.tree_registry.num_minted == 0, Errors::TreeNotEmpty);
+
+    let tree_address = ctx.accounts.tree.key();
+    let program_state = &mut ctx.accounts.program_state;
+    program_state.trees.retain(|tree| tree.tree_address != tree_address);
+    program_state.tree_count = program_state.trees.len() as u16;
+
+    emit!(TreeClosed {
+        tree: tree_address,
+        destination: ctx.accounts.destination.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::types::MetadataArgs;
+use crate::metadata::{leaf_schema, LeafMetadata};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LeafHashes {
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub leaf_hash: [u8; 32], // hash of the full leaf, as stored in the tree
+}
+
+#[derive(Accounts)]
+pub struct ComputeLeafHashes<'info> {
+    /// CHECK: Only hashed into the asset id
+    pub tree: UncheckedAccount<'info>,
+}
+
+// Read only, meant to be simulated by clients that need the hashes bubblegum would compute for a
+// leaf without reimplementing them
+pub fn compute_leaf_hashes(
+    ctx: Context<ComputeLeafHashes>,
+    metadata: LeafMetadata,
+    nonce: u64,
+    owner: Pubkey,
+    delegate: Pubkey,
+) -> Result<LeafHashes> {
+    verbose_msg!("compute_leaf_hashes: tree {}, nonce {}, owner {}, delegate {}", ctx.accounts.tree.key(), nonce, owner, delegate);
+    let metadata: MetadataArgs = metadata.into();
+    let leaf = leaf_schema(ctx.accounts.tree.key, owner, delegate, nonce, &metadata)?;
+
+    Ok(LeafHashes {
+        data_hash: leaf.data_hash(),
+        creator_hash: leaf.creator_hash(),
+        leaf_hash: leaf.hash(),
+    })
+}
@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::Token;
+use anchor_lang::error::ErrorCode;
+use mpl_bubblegum::{accounts::Voucher, instructions::DecompressV1CpiBuilder};
+use crate::{error::Errors, metadata::LeafMetadata, state::Config, Metadata, MplBubblegum, Noop};
+use crate::instructions::{frozen_marker_address, require_not_frozen};
+
+#[derive(Accounts)]
+pub struct DecompressV1<'info> {
+    /// CHECK: This account is closed in the downstream program
+    #[account(mut)]
+    pub voucher: UncheckedAccount<'info>,
+
+    // A leaf frozen after it was redeemed must not be decompressed either
+    /// CHECK: Checked against the voucher's tree and nonce in the handler, then only checked to be empty
+    pub frozen_marker: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub leaf_owner: Signer<'info>,
+
+    /// CHECK: Associated token account of the leaf owner, created in the downstream program
+    #[account(mut)]
+    pub token_account: UncheckedAccount<'info>,
+
+    // Asset id pda derived from the tree and leaf nonce by the bubblegum program
+    /// CHECK: This account is initialized in the downstream program
+    #[account(mut)]
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum pda derived from the mint
+    #[account(mut)]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    /// CHECK: This account is initialized in the downstream program
+    #[account(mut)]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    /// CHECK: This account is initialized in the downstream program
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+// Mints the spl token for a redeemed leaf. The metadata must match the leaf so bubblegum can
+// verify it against the voucher. Bubblegum creates trees with decompression disabled, so the
+// tree creator has to enable it with set_decompressible_state before a leaf can be redeemed here
+pub fn decompress_v1(ctx: Context<DecompressV1>, metadata: LeafMetadata) -> Result<()> {
+    verbose_msg!("decompress_v1: voucher {}, mint {}, leaf_owner {}", ctx.accounts.voucher.key(), ctx.accounts.mint.key(), ctx.accounts.leaf_owner.key());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let voucher = Voucher::try_from(&ctx.accounts.voucher.to_account_info())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    require_keys_eq!(
+        ctx.accounts.frozen_marker.key(),
+        frozen_marker_address(&voucher.merkle_tree, voucher.leaf_schema.nonce()),
+        ErrorCode::ConstraintSeeds
+    );
+    require_not_frozen(&ctx.accounts.frozen_marker)?;
+
+    DecompressV1CpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .voucher(&ctx.accounts.voucher.to_account_info())
+        .leaf_owner(&ctx.accounts.leaf_owner.to_account_info())
+        .token_account(&ctx.accounts.token_account.to_account_info())
+        .mint(&ctx.accounts.mint.to_account_info())
+        .mint_authority(&ctx.accounts.mint_authority.to_account_info())
+        .metadata_account(&ctx.accounts.metadata_account.to_account_info())
+        .master_edition(&ctx.accounts.master_edition.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .sysvar_rent(&ctx.accounts.rent.to_account_info())
+        .token_metadata_program(&ctx.accounts.token_metadata_program.to_account_info())
+        .token_program(&ctx.accounts.token_program.to_account_info())
+        .associated_token_program(&ctx.accounts.associated_token_program.to_account_info())
+        .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
+        .metadata(metadata.into())
+        .invoke()?;
+
+    Ok(())
+}
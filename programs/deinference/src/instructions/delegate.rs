@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::DelegateCpiBuilder;
+use crate::{error::Errors, state::Config, MplBubblegum, Noop, SplAccountCompression};
+use crate::proof::{is_bubblegum_tree, proof_accounts};
+
+#[derive(Accounts)]
+pub struct DelegateKnowledge<'info> {
+    /// CHECK: This account is checked in the downstream instruction
+    pub tree_config: UncheckedAccount<'info>,
+
+    pub leaf_owner: Signer<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub previous_leaf_delegate: UncheckedAccount<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub new_leaf_delegate: UncheckedAccount<'info>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut, constraint = is_bubblegum_tree(&tree, tree_config.key) @ Errors::InvalidTreeAccount)]
+    pub tree: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn delegate_knowledge<'info>(
+    ctx: Context<'_, '_, '_, 'info, DelegateKnowledge<'info>>,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+) -> Result<()> {
+    verbose_msg!("delegate_knowledge: tree {}, new_leaf_delegate {}, nonce {}, index {}", ctx.accounts.tree.key(), ctx.accounts.new_leaf_delegate.key(), nonce, index);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    // Re-delegating to the current delegate leaves the leaf unchanged
+    if ctx.accounts.previous_leaf_delegate.key() == ctx.accounts.new_leaf_delegate.key() {
+        return Ok(());
+    }
+
+    let proof = proof_accounts(ctx.remaining_accounts);
+
+    DelegateCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .leaf_owner(&ctx.accounts.leaf_owner.to_account_info())
+        .previous_leaf_delegate(&ctx.accounts.previous_leaf_delegate.to_account_info())
+        .new_leaf_delegate(&ctx.accounts.new_leaf_delegate.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
+        .compression_program(&ctx.accounts.compression_program.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .root(root)
+        .data_hash(data_hash)
+        .creator_hash(creator_hash)
+        .nonce(nonce)
+        .index(index)
+        .add_remaining_accounts(&proof)
+        .invoke()?;
+
+    Ok(())
+}
@@ -26,6 +26,7 @@ pub struct GetModel<'info>{
 }
 
 pub fn get_model(ctx: Context<GetModel>, weights_hash: [u8; 32]) -> Result<()> {
+    verbose_msg!("get_model: task_data {} bump {}, weights_hash {:?}", ctx.accounts.task_data.key(), ctx.bumps.task_data, weights_hash);
     let task_data = &ctx.accounts.task_data;
 
     let model = task_data.
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::accounts::TreeConfig;
+use crate::{error::Errors, state::is_valid_role, tree_authority_pda};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TreeAuthority {
+    pub tree_delegate: Pubkey,
+    pub is_tree_owner: bool, // whether this program's tree authority pda for the role is the tree creator or delegate
+}
+
+#[derive(Accounts)]
+pub struct GetTreeAuthority<'info> {
+    /// CHECK: Deserialized as a bubblegum tree config in the handler
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub tree: UncheckedAccount<'info>,
+}
+
+// Read only, meant to be simulated by clients to learn whether they can mint through this program
+// with the given role
+pub fn get_tree_authority(ctx: Context<GetTreeAuthority>, role: Vec<u8>) -> Result<TreeAuthority> {
+    verbose_msg!("get_tree_authority: tree_config {}, tree {}, role {:?}", ctx.accounts.tree_config.key(), ctx.accounts.tree.key(), role);
+    let tree_config = TreeConfig::try_from(&ctx.accounts.tree_config.to_account_info())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    // A tree config always belongs to the tree derived into its address
+    let (expected_config, _) = TreeConfig::find_pda(&ctx.accounts.tree.key());
+    require_keys_eq!(ctx.accounts.tree_config.key(), expected_config, ErrorCode::ConstraintSeeds);
+
+    require!(is_valid_role(&role), Errors::InvalidAuthoritySeeds);
+
+    // Bubblegum lets either the creator or the delegate mint, and the pda can be either one
+    let (tree_owner, _) = tree_authority_pda(&role, &ctx.accounts.tree.key());
+    Ok(TreeAuthority {
+        tree_delegate: tree_config.tree_delegate,
+        is_tree_owner: tree_config.tree_creator == tree_owner || tree_config.tree_delegate == tree_owner,
+    })
+}
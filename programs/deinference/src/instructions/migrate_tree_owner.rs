@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::{accounts::TreeConfig, instructions::SetTreeDelegateCpiBuilder};
+use crate::{error::Errors, merkle_tree::{canopy_depth, tree_dimensions}, state::{Config, MintAuthority, ProgramState, TreeInfo, TreeRegistry, MAX_TREES, TREE_OWNER_SEED}, MplBubblegum};
+
+#[event]
+pub struct TreeMigrated {
+    pub tree: Pubkey,
+    pub old_authority: Pubkey,
+    pub tree_owner: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct MigrateTreeOwner<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // Wallet recorded as tree creator in the tree config. A program that created the tree with one of
+    // its pdas can migrate it by invoking this instruction with that pda as a cpi signer
+    pub old_authority: Signer<'info>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Only its header is read to register the tree
+    pub tree: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [TREE_OWNER_SEED, tree.key().as_ref()],
+        bump
+    )]
+    /// CHECK: This account used as a signing PDA only
+    pub tree_owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"knowledge"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    // space = account disc (8) + creator (32) + max_depth (4) + max_buffer_size (4) + canopy_depth (4) + created_at (8) + num_minted (8) + collection_minted (8) + last_nonce (8) + num_appended (8)
+    #[account(
+        init_if_needed, payer = payer, space = 92, seeds = [b"registry", tree.key().as_ref()], bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    // space = account disc (8) + vec size (4) + max_#_minters (16) * pubkey (32)
+    #[account(
+        init_if_needed, payer = payer, space = 524, seeds = [b"mint_auth", tree.key().as_ref()], bump
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+// The old authority stays the tree creator and delegates minting to the current tree_owner pda.
+// Trees not yet known to this program are registered, with the old authority as the only minter
+pub fn migrate_tree_owner(ctx: Context<MigrateTreeOwner>) -> Result<()> {
+    verbose_msg!("migrate_tree_owner: tree_owner {} bump {}, tree {}, old_authority {}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key(), ctx.accounts.old_authority.key());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let tree_config = TreeConfig::try_from(&ctx.accounts.tree_config.to_account_info())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    require_keys_eq!(tree_config.tree_creator, ctx.accounts.old_authority.key(), Errors::Unauthorized);
+
+    SetTreeDelegateCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .tree_creator(&ctx.accounts.old_authority.to_account_info())
+        .new_tree_delegate(&ctx.accounts.tree_owner.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .invoke()?;
+
+    // init_if_needed zeroes the data of an account it creates, and every instruction registering a
+    // tree stores its depth, which bubblegum requires to be at least 3. So a zero depth means the
+    // registry was just created here and the tree is not yet known to this program
+    if ctx.accounts.tree_registry.max_depth == 0 {
+        let tree_data = ctx.accounts.tree.try_borrow_data()?;
+        let (max_depth, max_buffer_size) = tree_dimensions(&tree_data)
+            .ok_or(Errors::TreeAccountTooSmall)?;
+        let canopy_depth = canopy_depth(tree_data.len(), max_depth, max_buffer_size)
+            .ok_or(Errors::TreeAccountTooSmall)?;
+
+        let program_state = &mut ctx.accounts.program_state;
+        require!(program_state.trees.len() < MAX_TREES, Errors::TooManyTrees);
+        program_state.trees.push(TreeInfo {
+            tree_address: *ctx.accounts.tree.key,
+            tree_config: *ctx.accounts.tree_config.key,
+            current_index: u16::try_from(tree_config.num_minted).map_err(|_| Errors::CounterOverflow)?
+        });
+        program_state.tree_count += 1;
+
+        let tree_registry = &mut ctx.accounts.tree_registry;
+        tree_registry.creator = *ctx.accounts.old_authority.key;
+        tree_registry.max_depth = max_depth;
+        tree_registry.max_buffer_size = max_buffer_size;
+        tree_registry.canopy_depth = canopy_depth;
+        tree_registry.created_at = Clock::get()?.unix_timestamp;
+        tree_registry.num_minted = tree_config.num_minted;
+        tree_registry.num_appended = tree_config.num_minted;
+
+        ctx.accounts.mint_authority.minters = vec![*ctx.accounts.old_authority.key];
+    }
+
+    emit!(TreeMigrated {
+        tree: *ctx.accounts.tree.key,
+        old_authority: *ctx.accounts.old_authority.key,
+        tree_owner: *ctx.accounts.tree_owner.key,
+    });
+
+    Ok(())
+}
@@ -1,72 +1,289 @@
 use anchor_lang::prelude::*;
-use mpl_bubblegum::{instructions::MintV1CpiBuilder, types::{MetadataArgs, TokenProgramVersion, TokenStandard}};
-use crate::{MplBubblegum, Noop, SplAccountCompression};
+use mpl_bubblegum::{accounts::TreeConfig, instructions::MintV1CpiBuilder, types::{Creator, MetadataArgs, TokenProgramVersion, TokenStandard}};
+use crate::{error::Errors, merkle_tree::last_appended_leaf, metadata::KnowledgeCreator, proof::is_bubblegum_tree, state::{is_valid_role, tree_authority_seeds, Config, ContentRegistry, CreatorsConfig, MintAuthority, TreeRegistry, TREE_OWNER_SEED}, MplBubblegum, Noop, SplAccountCompression};
+
+pub const MAX_NAME_LENGTH: usize = 32;
+pub const MAX_URI_LENGTH: usize = 200;
+pub const MAX_SELLER_FEE_BASIS_POINTS: u16 = 10000;
+pub const MAX_CREATORS: usize = 5;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct KnowledgeMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<KnowledgeCreator>,
+}
+
+#[event]
+pub struct LeafMinted {
+    pub tree: Pubkey,
+    pub index: u32,
+    pub nonce: u64,
+    pub leaf_hash: [u8; 32],
+}
 
 #[derive(Accounts)]
-pub struct Mint<'info> {
+#[instruction(
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Vec<KnowledgeCreator>,
+    role: Vec<u8>,
+    content_hash: Option<[u8; 32]>,
+)]
+pub struct MintKnowledge<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
     // Mut so the # of NFTs minted for this tree can be incremented
     #[account(mut)]
-    /// CHECK: This account is neither written to nor read from. 
-    pub tree_auth: UncheckedAccount<'info>,
+    /// CHECK: Read for the next leaf nonce, checked and modified in the downstream program
+    pub tree_config: UncheckedAccount<'info>,
 
-    /// CHECK: unsafe
-    #[account(mut)]
+    /// CHECK: Checked to be a bubblegum tree, modified in the downstream program
+    #[account(
+        mut,
+        constraint = is_bubblegum_tree(&tree, tree_config.key) @ Errors::InvalidTreeAccount,
+        constraint = is_valid_role(&role) @ Errors::InvalidAuthoritySeeds
+    )]
     pub tree: UncheckedAccount<'info>,
 
+    // Must be the authority for the role the tree was created with
     #[account(
-        seeds = [b"tree_owner", tree.key().as_ref()],
+        seeds = [TREE_OWNER_SEED, role.as_ref(), tree.key().as_ref()],
         bump
     )]
     /// CHECK: This account used as a signing PDA only
     pub tree_owner: UncheckedAccount<'info>,
 
+    #[account(
+        mut,
+        seeds = [b"registry", tree.key().as_ref()],
+        bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    #[account(
+        seeds = [b"mint_auth", tree.key().as_ref()],
+        bump,
+        constraint = mint_authority.is_minter(payer.key) @ Errors::Unauthorized
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    // Need not be the payer, so leaves can be minted straight to other wallets
     /// CHECK: This account is neither written to nor read from.
     pub leaf_owner: UncheckedAccount<'info>,
 
+    // Defaults to the leaf owner when omitted
+    /// CHECK: This account is neither written to nor read from.
+    pub leaf_delegate: Option<UncheckedAccount<'info>>,
+
+    // Only needed when minting by content hash. The payer picks whose registration to trust by
+    // passing that authority's registry, its hash is checked against the content hash in the handler
+    #[account(
+        seeds = [b"content", content_registry.authority.as_ref(), content_registry.hash.as_ref()],
+        bump
+    )]
+    pub content_registry: Option<Account<'info, ContentRegistry>>,
+
+    // When passed, its royalties replace the inline seller fee and creators. Restricted to the
+    // payer's or the tree creator's config so a minter cannot attach another authority's royalties
+    #[account(
+        seeds = [b"creators_config", creators_config.authority.as_ref()],
+        bump,
+        constraint = creators_config.authority == payer.key() || creators_config.authority == tree_registry.creator @ Errors::Unauthorized
+    )]
+    pub creators_config: Option<Account<'info, CreatorsConfig>>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
     pub log_wrapper: Program<'info, Noop>,
     pub compression_program: Program<'info, SplAccountCompression>,
     pub bubblegum_program: Program<'info, MplBubblegum>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn mint(
-    ctx: Context<Mint>,
+// With a content hash, the uri argument is ignored and the registered uri is minted instead.
+// Likewise a creators config overrides the seller fee and creators arguments.
+// Only mutable leaves can later be changed with update_metadata
+pub fn mint_knowledge(
+    ctx: Context<MintKnowledge>,
     name: String,
     symbol: String,
     uri: String,
     seller_fee_basis_points: u16,
+    creators: Vec<KnowledgeCreator>,
+    role: Vec<u8>,
+    content_hash: Option<[u8; 32]>,
+    is_mutable: bool,
     ) -> Result<()> {
+    verbose_msg!("mint_knowledge: tree_owner {} bump {}, tree {}, leaf_owner {}, role {:?}, content_hash {:?}, is_mutable {}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key(), ctx.accounts.leaf_owner.key(), role, content_hash, is_mutable);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let uri = match (content_hash, &ctx.accounts.content_registry) {
+        (Some(hash), Some(registry)) if registry.hash == hash => registry.uri.clone(),
+        (Some(_), _) => return err!(Errors::ContentNotRegistered),
+        (None, _) => uri,
+    };
+    let (seller_fee_basis_points, creators) = match &ctx.accounts.creators_config {
+        Some(config) => (config.seller_fee_basis_points, config.creators.clone()),
+        None => (seller_fee_basis_points, creators),
+    };
+    validate_metadata(&name, &uri)?;
+    validate_royalties(seller_fee_basis_points, &creators)?;
+    ctx.accounts.tree_registry.ensure_capacity(1)?;
+    let nonce = next_leaf_nonce(&ctx.accounts.tree_config)?;
+
+    let metadata = knowledge_metadata_args(name, symbol, uri, seller_fee_basis_points, creators, is_mutable);
+    let tree_key = ctx.accounts.tree.key();
+    let [owner_seed, role_seed, tree_seed] = tree_authority_seeds(&role, &tree_key);
+    let leaf_delegate = ctx.accounts.leaf_delegate.as_ref().unwrap_or(&ctx.accounts.leaf_owner);
+
     MintV1CpiBuilder::new(
         &ctx.accounts.bubblegum_program.to_account_info()
-    ).tree_config(&ctx.accounts.tree_auth.to_account_info())
+    ).tree_config(&ctx.accounts.tree_config.to_account_info())
     .leaf_owner(&ctx.accounts.leaf_owner.to_account_info())
-    .leaf_delegate(&ctx.accounts.leaf_owner.to_account_info())
+    .leaf_delegate(&leaf_delegate.to_account_info())
     .merkle_tree(&ctx.accounts.tree.to_account_info())
     .payer(&ctx.accounts.payer.to_account_info())
     .tree_creator_or_delegate(&ctx.accounts.tree_owner.to_account_info())
     .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
     .compression_program(&ctx.accounts.compression_program.to_account_info())
     .system_program(&ctx.accounts.system_program.to_account_info())
-    .metadata(MetadataArgs {
+    .metadata(metadata)
+    .invoke_signed(&[&[
+        owner_seed,
+        role_seed,
+        tree_seed,
+        &[ctx.bumps.tree_owner],
+    ]])?;
+
+    ctx.accounts.tree_registry.record_mints(1)?;
+    ctx.accounts.tree_registry.last_nonce = nonce;
+
+    emit!(leaf_minted_event(&ctx.accounts.tree, nonce)?);
+
+    Ok(())
+}
+
+pub fn knowledge_metadata_args(
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Vec<KnowledgeCreator>,
+    is_mutable: bool,
+) -> MetadataArgs {
+    // Creators are minted unverified and verify themselves with verify_creator
+    let creators = creators
+        .into_iter()
+        .map(|creator| Creator {
+            address: creator.address,
+            verified: false,
+            share: creator.share,
+        })
+        .collect();
+
+    MetadataArgs {
         name,
         uri,
         symbol,
-        creators: vec![], // empty for now
+        creators,
         seller_fee_basis_points,
         primary_sale_happened: false,
-        is_mutable: false,
+        is_mutable,
         edition_nonce: Some(0),
         collection: None,
         uses: None,
         token_program_version: TokenProgramVersion::Original,
         token_standard: Some(TokenStandard::NonFungible),
-    }).invoke_signed(&[&[
-        b"tree_owner",
-        ctx.accounts.tree.key().as_ref(),
-        &[ctx.bumps.tree_owner],
-    ]])?;
+    }
+}
+
+// Bubblegum limits, checked up front so clients get a clear error
+pub fn validate_metadata(name: &str, uri: &str) -> Result<()> {
+    require!(!uri.is_empty(), Errors::EmptyUri);
+    require!(uri.len() <= MAX_URI_LENGTH, Errors::UriTooLong);
+    require!(name.len() <= MAX_NAME_LENGTH, Errors::NameTooLong);
+    Ok(())
+}
+
+// Mirrors bubblegum's royalty checks so they fail with a specific error before the cpi
+pub fn validate_royalties(seller_fee_basis_points: u16, creators: &[KnowledgeCreator]) -> Result<()> {
+    require!(seller_fee_basis_points <= MAX_SELLER_FEE_BASIS_POINTS, Errors::InvalidSellerFee);
+    require!(creators.len() <= MAX_CREATORS, Errors::TooManyCreators);
+    if !creators.is_empty() {
+        let total_share: u16 = creators.iter().map(|creator| creator.share as u16).sum();
+        require!(total_share == 100, Errors::InvalidCreatorShares);
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+// Builds the LeafMinted event from the leaf the mint cpi just appended to the tree
+pub fn leaf_minted_event(tree: &AccountInfo, nonce: u64) -> Result<LeafMinted> {
+    let (index, leaf_hash) = last_appended_leaf(&tree.try_borrow_data()?)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    Ok(LeafMinted {
+        tree: tree.key(),
+        index,
+        nonce,
+        leaf_hash,
+    })
+}
+
+// Bubblegum assigns the tree config's mint count as the nonce of the next leaf, which is also
+// its index since leaves are only appended
+pub fn next_leaf_nonce(tree_config: &AccountInfo) -> Result<u64> {
+    let tree_config = TreeConfig::try_from(tree_config)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    Ok(tree_config.num_minted)
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_metadata() {
+        assert!(validate_metadata("TEST-NFT", "https://example.com/knowledge.json").is_ok());
+
+        assert_eq!(validate_metadata("TEST-NFT", "").unwrap_err(), Errors::EmptyUri.into());
+
+        let long_uri = "a".repeat(MAX_URI_LENGTH + 1);
+        assert_eq!(validate_metadata("TEST-NFT", &long_uri).unwrap_err(), Errors::UriTooLong.into());
+
+        let long_name = "a".repeat(MAX_NAME_LENGTH + 1);
+        assert_eq!(validate_metadata(&long_name, "uri").unwrap_err(), Errors::NameTooLong.into());
+    }
+
+    fn creator(share: u8) -> KnowledgeCreator {
+        KnowledgeCreator {
+            address: Pubkey::new_unique(),
+            verified: false,
+            share,
+        }
+    }
+
+    #[test]
+    fn test_validate_royalties() {
+        assert!(validate_royalties(500, &[creator(60), creator(40)]).is_ok());
+        assert!(validate_royalties(MAX_SELLER_FEE_BASIS_POINTS, &[]).is_ok());
+
+        assert_eq!(
+            validate_royalties(500, &[creator(60), creator(39)]).unwrap_err(),
+            Errors::InvalidCreatorShares.into()
+        );
+        assert_eq!(
+            validate_royalties(10001, &[creator(100)]).unwrap_err(),
+            Errors::InvalidSellerFee.into()
+        );
+        assert_eq!(
+            validate_royalties(500, &[creator(20), creator(20), creator(20), creator(20), creator(10), creator(10)]).unwrap_err(),
+            Errors::TooManyCreators.into()
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::MintV1CpiBuilder;
+use crate::instructions::{knowledge_metadata_args, next_leaf_nonce, validate_metadata, validate_royalties, KnowledgeMetadata, leaf_minted_event};
+use crate::{error::Errors, proof::is_bubblegum_tree, state::{is_valid_role, tree_authority_seeds, Config, MintAuthority, TreeRegistry, TREE_OWNER_SEED}, MplBubblegum, Noop, SplAccountCompression};
+
+// Each MintV1 cpi into a depth 14 tree costs roughly 30-40k compute units, so the default 200k
+// limit fits about 4 mints. A full batch of 8 needs a compute budget instruction requesting ~400k,
+// and short uris to stay within the 1232 byte transaction size limit.
+pub const MAX_BATCH_SIZE: usize = 8;
+
+#[derive(Accounts)]
+#[instruction(items: Vec<KnowledgeMetadata>, role: Vec<u8>)]
+pub struct MintBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    /// CHECK: Read for the next leaf nonce, checked and modified in the downstream program
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(
+        mut,
+        constraint = is_bubblegum_tree(&tree, tree_config.key) @ Errors::InvalidTreeAccount,
+        constraint = is_valid_role(&role) @ Errors::InvalidAuthoritySeeds
+    )]
+    pub tree: UncheckedAccount<'info>,
+
+    // Must be the authority for the role the tree was created with
+    #[account(
+        seeds = [TREE_OWNER_SEED, role.as_ref(), tree.key().as_ref()],
+        bump
+    )]
+    /// CHECK: This account used as a signing PDA only
+    pub tree_owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", tree.key().as_ref()],
+        bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    #[account(
+        seeds = [b"mint_auth", tree.key().as_ref()],
+        bump,
+        constraint = mint_authority.is_minter(payer.key) @ Errors::Unauthorized
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub leaf_owner: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn mint_batch(ctx: Context<MintBatch>, items: Vec<KnowledgeMetadata>, role: Vec<u8>) -> Result<()> {
+    verbose_msg!("mint_batch: tree_owner {} bump {}, tree {}, items {}, role {:?}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key(), items.len(), role);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    require!(!items.is_empty() && items.len() <= MAX_BATCH_SIZE, Errors::InvalidBatchSize);
+
+    // Validate the whole batch before minting anything
+    let count = items.len() as u64;
+    ctx.accounts.tree_registry.ensure_capacity(count)?;
+    for item in &items {
+        validate_metadata(&item.name, &item.uri)?;
+        validate_royalties(item.seller_fee_basis_points, &item.creators)?;
+    }
+
+    let tree_key = ctx.accounts.tree.key();
+    let [owner_seed, role_seed, tree_seed] = tree_authority_seeds(&role, &tree_key);
+    let signer_seeds: &[&[u8]] = &[
+        owner_seed,
+        role_seed,
+        tree_seed,
+        &[ctx.bumps.tree_owner],
+    ];
+    let first_nonce = next_leaf_nonce(&ctx.accounts.tree_config)?;
+
+    for (nonce, item) in (first_nonce..).zip(items) {
+        let metadata = knowledge_metadata_args(item.name, item.symbol, item.uri, item.seller_fee_basis_points, item.creators, false);
+
+        MintV1CpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+            .tree_config(&ctx.accounts.tree_config.to_account_info())
+            .leaf_owner(&ctx.accounts.leaf_owner.to_account_info())
+            .leaf_delegate(&ctx.accounts.leaf_owner.to_account_info())
+            .merkle_tree(&ctx.accounts.tree.to_account_info())
+            .payer(&ctx.accounts.payer.to_account_info())
+            .tree_creator_or_delegate(&ctx.accounts.tree_owner.to_account_info())
+            .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
+            .compression_program(&ctx.accounts.compression_program.to_account_info())
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .metadata(metadata)
+            .invoke_signed(&[signer_seeds])?;
+
+        emit!(leaf_minted_event(&ctx.accounts.tree, nonce)?);
+        ctx.accounts.tree_registry.last_nonce = nonce;
+    }
+
+    ctx.accounts.tree_registry.record_mints(count)?;
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::MintToCollectionV1CpiBuilder;
+use mpl_bubblegum::types::{Collection, MetadataArgs, TokenProgramVersion, TokenStandard};
+use crate::instructions::{next_leaf_nonce, validate_metadata, validate_royalties, leaf_minted_event};
+use crate::state::{is_valid_role, tree_authority_seeds, Config, MintAuthority, TreeRegistry, TREE_OWNER_SEED};
+use crate::{error::Errors, proof::is_bubblegum_tree, MplBubblegum, Noop, SplAccountCompression, Metadata};
+
+#[derive(Accounts)]
+#[instruction(name: String, symbol: String, uri: String, seller_fee_basis_points: u16, role: Vec<u8>)]
+pub struct MintToCollection<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Read for the next leaf nonce, checked and modified in the downstream program
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(
+        mut,
+        constraint = is_bubblegum_tree(&tree, tree_config.key) @ Errors::InvalidTreeAccount,
+        constraint = is_valid_role(&role) @ Errors::InvalidAuthoritySeeds
+    )]
+    pub tree: UncheckedAccount<'info>,
+
+    // Must be the authority for the role the tree was created with
+    #[account(
+        seeds = [TREE_OWNER_SEED, role.as_ref(), tree.key().as_ref()],
+        bump
+    )]
+    /// CHECK: This account used as a signing PDA only
+    pub tree_owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", tree.key().as_ref()],
+        bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    #[account(
+        seeds = [b"mint_auth", tree.key().as_ref()],
+        bump,
+        constraint = mint_authority.is_minter(payer.key) @ Errors::Unauthorized
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub leaf_owner: UncheckedAccount<'info>,
+
+    // Update authority of the collection
+    pub collection_authority: Signer<'info>,
+
+    /// CHECK: This account is checked in the downstream instruction
+    pub collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: This account is checked in the downstream instruction
+    #[account(mut)]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: This account is checked in the downstream instruction
+    pub collection_edition: UncheckedAccount<'info>,
+
+    /// CHECK: This is just used as a signing PDA.
+    pub bubblegum_signer: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn mint_to_collection(
+    ctx: Context<MintToCollection>,
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    role: Vec<u8>,
+) -> Result<()> {
+    verbose_msg!("mint_to_collection: tree_owner {} bump {}, tree {}, collection_mint {}, role {:?}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key(), ctx.accounts.collection_mint.key(), role);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    validate_metadata(&name, &uri)?;
+    validate_royalties(seller_fee_basis_points, &[])?;
+    ctx.accounts.tree_registry.ensure_capacity(1)?;
+    let nonce = next_leaf_nonce(&ctx.accounts.tree_config)?;
+
+    // Bubblegum marks the collection verified once the collection authority signs
+    let metadata = MetadataArgs {
+        name,
+        symbol,
+        uri,
+        creators: vec![],
+        seller_fee_basis_points,
+        primary_sale_happened: false,
+        is_mutable: false,
+        edition_nonce: Some(0),
+        uses: None,
+        collection: Some(Collection {
+            verified: false,
+            key: ctx.accounts.collection_mint.key(),
+        }),
+        token_program_version: TokenProgramVersion::Original,
+        token_standard: Some(TokenStandard::NonFungible),
+    };
+
+    let tree_key = ctx.accounts.tree.key();
+    let [owner_seed, role_seed, tree_seed] = tree_authority_seeds(&role, &tree_key);
+
+    MintToCollectionV1CpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .leaf_owner(&ctx.accounts.leaf_owner.to_account_info())
+        .leaf_delegate(&ctx.accounts.leaf_owner.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .payer(&ctx.accounts.payer.to_account_info())
+        .tree_creator_or_delegate(&ctx.accounts.tree_owner.to_account_info())
+        .collection_authority(&ctx.accounts.collection_authority.to_account_info())
+        .collection_authority_record_pda(Some(&ctx.accounts.bubblegum_program.to_account_info()))
+        .collection_mint(&ctx.accounts.collection_mint.to_account_info())
+        .collection_metadata(&ctx.accounts.collection_metadata.to_account_info())
+        .collection_edition(&ctx.accounts.collection_edition.to_account_info())
+        .bubblegum_signer(&ctx.accounts.bubblegum_signer.to_account_info())
+        .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
+        .compression_program(&ctx.accounts.compression_program.to_account_info())
+        .token_metadata_program(&ctx.accounts.token_metadata_program.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .metadata(metadata)
+        .invoke_signed(&[&[
+            owner_seed,
+            role_seed,
+            tree_seed,
+            &[ctx.bumps.tree_owner]
+        ]])?;
+
+    ctx.accounts.tree_registry.record_mints(1)?;
+    ctx.accounts.tree_registry.record_collection_mint()?;
+
+    ctx.accounts.tree_registry.last_nonce = nonce;
+
+    emit!(leaf_minted_event(&ctx.accounts.tree, nonce)?);
+
+    Ok(())
+}
@@ -2,7 +2,8 @@ use anchor_lang::prelude::*;
 use mpl_bubblegum::instructions::MintToCollectionV1CpiBuilder;
 use mpl_bubblegum::types::{Collection, MetadataArgs, TokenProgramVersion, TokenStandard};
 use crate::error::Errors;
-use crate::state::{ModelData, ProgramState, TaskData};
+use crate::proof::is_bubblegum_tree;
+use crate::state::{Config, MintAuthority, ModelData, ProgramState, TaskData, TreeRegistry, TREE_OWNER_SEED};
 use crate::{MplBubblegum, Noop, SplAccountCompression, Metadata};
 use crate::verify::verify_ed25519_instruction;
 
@@ -33,17 +34,31 @@ pub struct MintToTask<'info> {
     /// CHECK: This account is neither written to nor read from.
     pub model_owner: AccountInfo<'info>,
 
-    #[account(mut)]
-    /// CHECK: unsafe
+    /// CHECK: Checked to be a bubblegum tree, modified in the downstream program
+    #[account(mut, constraint = is_bubblegum_tree(&tree, tree_auth.key) @ Errors::InvalidTreeAccount)]
     pub tree: UncheckedAccount<'info>,
 
     #[account(
-        seeds = [b"tree_owner", tree.key().as_ref()],
+        seeds = [TREE_OWNER_SEED, tree.key().as_ref()],
         bump
     )]
     /// CHECK: unsafe
     pub collection_authority: UncheckedAccount<'info>,
 
+    #[account(
+        mut,
+        seeds = [b"registry", tree.key().as_ref()],
+        bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    #[account(
+        seeds = [b"mint_auth", tree.key().as_ref()],
+        bump,
+        constraint = mint_authority.is_minter(payer.key) @ Errors::Unauthorized
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
     /// CHECK: This account is checked in the instruction
     pub collection_mint: UncheckedAccount<'info>,
 
@@ -60,6 +75,10 @@ pub struct MintToTask<'info> {
 
     /// CHECK: This is just used as a signing PDA.
     pub bubblegum_signer: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
     pub log_wrapper: Program<'info, Noop>,
     pub compression_program: Program<'info, SplAccountCompression>,
     pub token_metadata_program: Program<'info, Metadata>,
@@ -68,6 +87,10 @@ pub struct MintToTask<'info> {
 }
 
 pub fn mint_to_task(ctx: Context<MintToTask>, name: String, symbol: String, weights: String, weights_signature: [u8; 64], seller_fee_basis_points: u16) -> Result<()> {
+    verbose_msg!("mint_to_task: collection_authority {} bump {}, tree {}, collection_mint {}", ctx.accounts.collection_authority.key(), ctx.bumps.collection_authority, ctx.accounts.tree.key(), ctx.accounts.collection_mint.key());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    ctx.accounts.tree_registry.ensure_capacity(1)?;
+
     let weights_clone = weights.clone();
     MintToCollectionV1CpiBuilder::new(
         &ctx.accounts.bubblegum_program.to_account_info(),
@@ -108,7 +131,7 @@ pub fn mint_to_task(ctx: Context<MintToTask>, name: String, symbol: String, weig
             }
         )
         .invoke_signed(&[&[
-            b"tree_owner",
+            TREE_OWNER_SEED,
             ctx.accounts.tree.key().as_ref(),
             &[ctx.bumps.collection_authority]
     ]])?;
@@ -135,7 +158,7 @@ pub fn mint_to_task(ctx: Context<MintToTask>, name: String, symbol: String, weig
     let model = ModelData {
         weights_hash: weights_clone.as_bytes().try_into().expect("URI must be 32 bytes"),
         tree_address: *ctx.accounts.tree.key,
-        leaf_index: leaf_index,
+        leaf_index,
         reputation: 1 // inital value
     };
 
@@ -143,6 +166,7 @@ pub fn mint_to_task(ctx: Context<MintToTask>, name: String, symbol: String, weig
 
     // Increment tree index
     program_state.increment_index(*tree.key)?;
+    ctx.accounts.tree_registry.record_mints(1)?;
     
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::{error::Errors, state::{Config, MintAuthority, TreeRegistry}};
+
+// Shared by add_minter and remove_minter
+#[derive(Accounts)]
+pub struct UpdateMinters<'info> {
+    // Wallet that created the tree through this program
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry", tree.key().as_ref()],
+        bump,
+        has_one = creator @ Errors::Unauthorized
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"mint_auth", tree.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub tree: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+}
+
+pub fn add_minter(ctx: Context<UpdateMinters>, minter: Pubkey) -> Result<()> {
+    verbose_msg!("add_minter: mint_authority {} bump {}, minter {}", ctx.accounts.mint_authority.key(), ctx.bumps.mint_authority, minter);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    ctx.accounts.mint_authority.add_minter(minter)
+}
+
+pub fn remove_minter(ctx: Context<UpdateMinters>, minter: Pubkey) -> Result<()> {
+    verbose_msg!("remove_minter: mint_authority {} bump {}, minter {}", ctx.accounts.mint_authority.key(), ctx.bumps.mint_authority, minter);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    ctx.accounts.mint_authority.remove_minter(&minter)
+}
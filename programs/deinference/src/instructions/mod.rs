@@ -1,4 +1,6 @@
-pub mod setup;
+// Program and tree setup instructions are grouped in setup, and re-exported with the rest
+// so every handler and accounts struct is reachable as crate::instructions::*
+mod setup;
 mod mint;
 mod mint_to_task;
 mod close_account;
@@ -6,11 +8,68 @@ mod get_model;
 mod post_request;
 mod submit_pred;
 mod aggregate;
+mod transfer;
+mod burn;
+mod delegate;
+mod set_tree_owner;
+mod mint_to_collection;
+mod mint_batch;
+mod redeem;
+mod decompress;
+mod verify_creator;
+mod update_metadata;
+mod verify_leaf;
+mod append_leaf;
+mod close_tree;
+mod minters;
+mod freeze;
+mod set_collection_size;
+mod transfer_batch;
+mod get_tree_authority;
+mod migrate_tree_owner;
+mod register_content;
+mod remaining_capacity;
+mod backfill_registry;
+mod set_decompressible_state;
+mod require_collection_membership;
+mod pending_owner;
+mod set_creators_config;
+mod compute_leaf_hashes;
+mod set_paused;
 
+pub use setup::*;
 pub use mint::*;
 pub use close_account::*;
 pub use mint_to_task::*;
 pub use get_model::*;
 pub use post_request::*;
 pub use submit_pred::*;
-pub use aggregate::*;
\ No newline at end of file
+pub use aggregate::*;
+pub use transfer::*;
+pub use burn::*;
+pub use delegate::*;
+pub use set_tree_owner::*;
+pub use mint_to_collection::*;
+pub use mint_batch::*;
+pub use redeem::*;
+pub use decompress::*;
+pub use verify_creator::*;
+pub use update_metadata::*;
+pub use verify_leaf::*;
+pub use append_leaf::*;
+pub use close_tree::*;
+pub use minters::*;
+pub use freeze::*;
+pub use set_collection_size::*;
+pub use transfer_batch::*;
+pub use get_tree_authority::*;
+pub use migrate_tree_owner::*;
+pub use register_content::*;
+pub use remaining_capacity::*;
+pub use backfill_registry::*;
+pub use set_decompressible_state::*;
+pub use require_collection_membership::*;
+pub use pending_owner::*;
+pub use set_creators_config::*;
+pub use compute_leaf_hashes::*;
+pub use set_paused::*;
\ No newline at end of file
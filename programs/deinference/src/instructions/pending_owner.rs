@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::SetTreeDelegateCpiBuilder;
+use crate::{error::Errors, state::{is_valid_role, tree_authority_seeds, Config, PendingOwner, TreeRegistry, TREE_OWNER_SEED}, MplBubblegum};
+
+#[derive(Accounts)]
+pub struct ProposeTreeOwner<'info> {
+    // Wallet that created the tree through this program
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry", tree.key().as_ref()],
+        bump,
+        has_one = creator @ Errors::Unauthorized
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    // space = account disc (8) + creator (32) + proposed (32)
+    #[account(
+        init, payer = creator, space = 72, seeds = [b"pending_owner", tree.key().as_ref()], bump
+    )]
+    pub pending_owner: Account<'info, PendingOwner>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub tree: UncheckedAccount<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub proposed: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: Vec<u8>)]
+pub struct AcceptTreeOwner<'info> {
+    // Must be the proposed delegate, so a mistyped key can never accept
+    pub proposed: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_owner", tree.key().as_ref()],
+        bump,
+        has_one = proposed @ Errors::Unauthorized,
+        has_one = creator @ Errors::Unauthorized,
+        close = creator
+    )]
+    pub pending_owner: Account<'info, PendingOwner>,
+
+    /// CHECK: Only receives the pending record's rent
+    #[account(mut)]
+    pub creator: UncheckedAccount<'info>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: This account is checked in the downstream instruction
+    #[account(constraint = is_valid_role(&role) @ Errors::InvalidAuthoritySeeds)]
+    pub tree: UncheckedAccount<'info>,
+
+    // Must be the authority for the role the tree was created with
+    #[account(
+        seeds = [TREE_OWNER_SEED, role.as_ref(), tree.key().as_ref()],
+        bump
+    )]
+    /// CHECK: This account used as a signing PDA only
+    pub tree_owner: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTreeOwner<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_owner", tree.key().as_ref()],
+        bump,
+        has_one = creator @ Errors::Unauthorized,
+        close = creator
+    )]
+    pub pending_owner: Account<'info, PendingOwner>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub tree: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+}
+
+// First step of handing the tree delegate to a new key, nothing changes until it accepts
+pub fn propose_tree_owner(ctx: Context<ProposeTreeOwner>) -> Result<()> {
+    verbose_msg!("propose_tree_owner: pending_owner {} bump {}, tree {}, proposed {}", ctx.accounts.pending_owner.key(), ctx.bumps.pending_owner, ctx.accounts.tree.key(), ctx.accounts.proposed.key());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let pending_owner = &mut ctx.accounts.pending_owner;
+    pending_owner.creator = *ctx.accounts.creator.key;
+    pending_owner.proposed = *ctx.accounts.proposed.key;
+    Ok(())
+}
+
+// The tree_owner pda stays the tree creator, only minting rights are delegated
+pub fn accept_tree_owner(ctx: Context<AcceptTreeOwner>, role: Vec<u8>) -> Result<()> {
+    verbose_msg!("accept_tree_owner: tree_owner {} bump {}, tree {}, proposed {}, role {:?}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key(), ctx.accounts.proposed.key(), role);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let tree_key = ctx.accounts.tree.key();
+    let [owner_seed, role_seed, tree_seed] = tree_authority_seeds(&role, &tree_key);
+
+    SetTreeDelegateCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .tree_creator(&ctx.accounts.tree_owner.to_account_info())
+        .new_tree_delegate(&ctx.accounts.proposed.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .invoke_signed(&[&[
+            owner_seed,
+            role_seed,
+            tree_seed,
+            &[ctx.bumps.tree_owner]
+        ]])?;
+
+    Ok(())
+}
+
+// The pending record is closed by the accounts constraint
+pub fn cancel_tree_owner(ctx: Context<CancelTreeOwner>) -> Result<()> {
+    verbose_msg!("cancel_tree_owner: pending_owner {} bump {}, tree {}", ctx.accounts.pending_owner.key(), ctx.bumps.pending_owner, ctx.accounts.tree.key());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
-use crate::{error::Errors, state::{InferenceRequest, ProgramState, RequestStatus, TaskData}};
+use crate::error::Errors;
+use crate::state::{Config, InferenceRequest, ProgramState, RequestStatus, TaskData};
 
 #[event]
 pub struct Request {
@@ -42,10 +43,16 @@ pub struct PostRequest<'info> {
 
     /// CHECK: unsafe
     pub collection_mint: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
     pub system_program: Program<'info, System>,
 }
 
 pub fn post_request(ctx: Context<PostRequest>, request_id: u16, request_data: Vec<u8>, required_predictions: u16) -> Result<()> {
+    verbose_msg!("post_request: request_state {} bump {}, request_id {}", ctx.accounts.request_state.key(), ctx.bumps.request_state, request_id);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
     let task_data = &ctx.accounts.task_data;
 
     // Verify the collection mint matches the task data account
@@ -58,16 +65,16 @@ pub fn post_request(ctx: Context<PostRequest>, request_id: u16, request_data: Ve
     let posted_at = clock.unix_timestamp;
 
     emit!(Request{
-        request_id: request_id,
+        request_id,
         task_collection: *ctx.accounts.collection_mint.key,
         request_data: request_data.clone(),
-        posted_at: posted_at,
+        posted_at,
         status: RequestStatus::Pending
     });
 
     // Store state info
     let request_state = &mut ctx.accounts.request_state;
-    request_state.input_data = request_data.try_into().expect("Vec length matched array length"); 
+    request_state.input_data = request_data;
     request_state.posted_at = posted_at;
     request_state.status = RequestStatus::Pending;
     request_state.task_collection = *ctx.accounts.collection_mint.key;
@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::{CancelRedeemCpiBuilder, RedeemCpiBuilder};
+use crate::{error::Errors, state::{Config, TreeRegistry}, MplBubblegum, Noop, SplAccountCompression};
+use crate::instructions::require_not_frozen;
+use crate::proof::proof_accounts;
+
+#[derive(Accounts)]
+#[instruction(root: [u8; 32], data_hash: [u8; 32], creator_hash: [u8; 32], nonce: u64)]
+pub struct Redeem<'info> {
+    /// CHECK: This account is checked in the downstream instruction
+    pub tree_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub leaf_owner: Signer<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub leaf_delegate: UncheckedAccount<'info>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut)]
+    pub tree: UncheckedAccount<'info>,
+
+    /// CHECK: Only checked to be empty, a leaf is frozen while this account exists
+    #[account(
+        seeds = [b"frozen", tree.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
+    pub frozen_marker: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", tree.key().as_ref()],
+        bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    // Pda derived from the tree and leaf nonce by the bubblegum program
+    /// CHECK: This account is initialized in the downstream program
+    #[account(mut)]
+    pub voucher: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRedeem<'info> {
+    /// CHECK: This account is checked in the downstream instruction
+    pub tree_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub leaf_owner: Signer<'info>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut)]
+    pub tree: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", tree.key().as_ref()],
+        bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    /// CHECK: This account is closed in the downstream program
+    #[account(mut)]
+    pub voucher: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+// Moves the leaf out of the tree into a voucher that can be decompressed or cancelled
+pub fn redeem<'info>(
+    ctx: Context<'_, '_, '_, 'info, Redeem<'info>>,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+) -> Result<()> {
+    verbose_msg!("redeem: tree_registry {} bump {}, tree {}, nonce {}, index {}", ctx.accounts.tree_registry.key(), ctx.bumps.tree_registry, ctx.accounts.tree.key(), nonce, index);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    require_not_frozen(&ctx.accounts.frozen_marker)?;
+    let proof = proof_accounts(ctx.remaining_accounts);
+
+    RedeemCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .leaf_owner(&ctx.accounts.leaf_owner.to_account_info())
+        .leaf_delegate(&ctx.accounts.leaf_delegate.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .voucher(&ctx.accounts.voucher.to_account_info())
+        .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
+        .compression_program(&ctx.accounts.compression_program.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .root(root)
+        .data_hash(data_hash)
+        .creator_hash(creator_hash)
+        .nonce(nonce)
+        .index(index)
+        .add_remaining_accounts(&proof)
+        .invoke()?;
+
+    ctx.accounts.tree_registry.record_removal()?;
+
+    Ok(())
+}
+
+// Puts a redeemed but not yet decompressed leaf back into the tree
+pub fn cancel_redeem<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelRedeem<'info>>,
+    root: [u8; 32],
+) -> Result<()> {
+    verbose_msg!("cancel_redeem: tree_registry {} bump {}, tree {}", ctx.accounts.tree_registry.key(), ctx.bumps.tree_registry, ctx.accounts.tree.key());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let proof = proof_accounts(ctx.remaining_accounts);
+
+    CancelRedeemCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .leaf_owner(&ctx.accounts.leaf_owner.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .voucher(&ctx.accounts.voucher.to_account_info())
+        .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
+        .compression_program(&ctx.accounts.compression_program.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .root(root)
+        .add_remaining_accounts(&proof)
+        .invoke()?;
+
+    ctx.accounts.tree_registry.record_restore()?;
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::{error::Errors, instructions::MAX_URI_LENGTH, state::{Config, ContentRegistry}};
+
+#[event]
+pub struct ContentRegistered {
+    pub hash: [u8; 32],
+    pub uri: String,
+}
+
+#[derive(Accounts)]
+#[instruction(hash: [u8; 32])]
+pub struct RegisterContent<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // space = account disc (8) + hash (32) + authority (32) + string prefix (4) + max uri length (200)
+    #[account(
+        init, payer = authority, space = 276, seeds = [b"content", authority.key().as_ref(), hash.as_ref()], bump
+    )]
+    pub content_registry: Account<'info, ContentRegistry>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn register_content(ctx: Context<RegisterContent>, hash: [u8; 32], uri: String) -> Result<()> {
+    verbose_msg!("register_content: content_registry {} bump {}, hash {:?}", ctx.accounts.content_registry.key(), ctx.bumps.content_registry, hash);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    require!(!uri.is_empty(), Errors::EmptyUri);
+    require!(uri.len() <= MAX_URI_LENGTH, Errors::UriTooLong);
+
+    let content_registry = &mut ctx.accounts.content_registry;
+    content_registry.hash = hash;
+    content_registry.authority = *ctx.accounts.authority.key;
+    content_registry.uri = uri.clone();
+
+    emit!(ContentRegistered { hash, uri });
+
+    Ok(())
+}
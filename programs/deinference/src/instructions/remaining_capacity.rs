@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::{error::Errors, merkle_tree::{appended_leaf_count, tree_dimensions}, state::TreeRegistry};
+
+#[derive(Accounts)]
+pub struct RemainingCapacity<'info> {
+    /// CHECK: Only its header and rightmost path are read
+    pub tree: UncheckedAccount<'info>,
+
+    // Trees created before the registry existed are omitted and read from the tree instead
+    #[account(
+        seeds = [b"registry", tree.key().as_ref()],
+        bump
+    )]
+    pub tree_registry: Option<Account<'info, TreeRegistry>>,
+}
+
+// Returns 2^max_depth - appended leaves as return data, burned leaves still take their slot
+pub fn remaining_capacity(ctx: Context<RemainingCapacity>) -> Result<u64> {
+    verbose_msg!("remaining_capacity: tree_registry bump {:?}, tree {}", ctx.bumps.tree_registry, ctx.accounts.tree.key());
+    if let Some(tree_registry) = &ctx.accounts.tree_registry {
+        return Ok(tree_registry.remaining_capacity());
+    }
+
+    // Leaves are only appended, so the append count is the number minted
+    let tree_data = ctx.accounts.tree.try_borrow_data()?;
+    let (max_depth, _) = tree_dimensions(&tree_data).ok_or(Errors::TreeAccountTooSmall)?;
+    let appended = appended_leaf_count(&tree_data).ok_or(Errors::TreeAccountTooSmall)?;
+    let capacity = 1u64.checked_shl(max_depth).unwrap_or(u64::MAX);
+    Ok(capacity.saturating_sub(appended as u64))
+}
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::{instructions::VerifyLeafCpiBuilder, types::MetadataArgs};
+use crate::{error::Errors, metadata::{leaf_schema, LeafMetadata}, SplAccountCompression};
+use crate::proof::{is_bubblegum_tree, proof_accounts, require_proof_length};
+
+#[derive(Accounts)]
+pub struct RequireCollectionMembership<'info> {
+    /// CHECK: Only used to check the tree is a bubblegum tree
+    pub tree_config: UncheckedAccount<'info>,
+
+    // Any compression tree could hold a forged leaf with a verified collection, so only trees
+    // bubblegum owns are trusted
+    /// CHECK: Checked to be a bubblegum tree, proven against in the downstream program
+    #[account(constraint = is_bubblegum_tree(&tree, tree_config.key) @ Errors::InvalidTreeAccount)]
+    pub tree: UncheckedAccount<'info>,
+
+    /// CHECK: Only hashed into the leaf
+    pub leaf_owner: UncheckedAccount<'info>,
+
+    /// CHECK: Only hashed into the leaf
+    pub leaf_delegate: UncheckedAccount<'info>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+}
+
+// Rebuilds the leaf from its metadata and proves it against root with the proof in
+// remaining_accounts, then checks the metadata's verified collection. Other programs can cpi
+// into this to gate actions on collection membership
+pub fn require_collection_membership<'info>(
+    ctx: Context<'_, '_, '_, 'info, RequireCollectionMembership<'info>>,
+    root: [u8; 32],
+    nonce: u64,
+    index: u32,
+    metadata: LeafMetadata,
+    collection_mint: Pubkey,
+) -> Result<()> {
+    verbose_msg!("require_collection_membership: tree {}, nonce {}, index {}, collection_mint {}", ctx.accounts.tree.key(), nonce, index, collection_mint);
+    require_proof_length(&ctx.accounts.tree, ctx.remaining_accounts)?;
+    let proof = proof_accounts(ctx.remaining_accounts);
+
+    let in_collection = is_collection_member(&metadata, &collection_mint);
+    let metadata: MetadataArgs = metadata.into();
+    let leaf = leaf_schema(
+        ctx.accounts.tree.key,
+        ctx.accounts.leaf_owner.key(),
+        ctx.accounts.leaf_delegate.key(),
+        nonce,
+        &metadata,
+    )?;
+
+    VerifyLeafCpiBuilder::new(&ctx.accounts.compression_program.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .root(root)
+        .leaf(leaf.hash())
+        .index(index)
+        .add_remaining_accounts(&proof)
+        .invoke()?;
+
+    require!(in_collection, Errors::NotInCollection);
+
+    Ok(())
+}
+
+// Unverified collections can be set by anyone at mint, so only a verified one counts
+pub fn is_collection_member(metadata: &LeafMetadata, collection_mint: &Pubkey) -> bool {
+    metadata
+        .collection
+        .as_ref()
+        .is_some_and(|collection| collection.verified && collection.key == *collection_mint)
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::KnowledgeCollection;
+
+    fn leaf_metadata(collection: Option<KnowledgeCollection>) -> LeafMetadata {
+        LeafMetadata {
+            name: "knowledge".to_string(),
+            symbol: "KNOW".to_string(),
+            uri: "https://example.com".to_string(),
+            seller_fee_basis_points: 0,
+            primary_sale_happened: false,
+            is_mutable: false,
+            edition_nonce: Some(0),
+            collection,
+            creators: vec![],
+        }
+    }
+
+    #[test]
+    fn test_is_collection_member() {
+        let collection_mint = Pubkey::new_unique();
+
+        let verified = leaf_metadata(Some(KnowledgeCollection { verified: true, key: collection_mint }));
+        assert!(is_collection_member(&verified, &collection_mint));
+        assert!(!is_collection_member(&verified, &Pubkey::new_unique()));
+
+        let unverified = leaf_metadata(Some(KnowledgeCollection { verified: false, key: collection_mint }));
+        assert!(!is_collection_member(&unverified, &collection_mint));
+        assert!(!is_collection_member(&leaf_metadata(None), &collection_mint));
+    }
+}
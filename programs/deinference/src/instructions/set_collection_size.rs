@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use mpl_token_metadata::accounts::Metadata as MetadataAccount;
+use mpl_token_metadata::instructions::SetCollectionSizeCpiBuilder;
+use mpl_token_metadata::types::SetCollectionSizeArgs;
+use crate::{error::Errors, state::Config, Metadata};
+
+#[derive(Accounts)]
+pub struct SetCollectionSize<'info> {
+    // Must be the update authority of the collection
+    pub collection_authority: Signer<'info>,
+
+    /// CHECK: This account is checked in the downstream instruction
+    pub collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Deserialized to check the update authority, then modified in the downstream program
+    #[account(mut)]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+// Token metadata only accepts this once, to size a collection that was created unsized.
+// Afterwards it tracks the size itself as items are verified
+pub fn set_collection_size(ctx: Context<SetCollectionSize>, size: u64) -> Result<()> {
+    verbose_msg!("set_collection_size: collection_mint {}, size {}", ctx.accounts.collection_mint.key(), size);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let collection_metadata = MetadataAccount::try_from(&ctx.accounts.collection_metadata.to_account_info())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    require_keys_eq!(
+        collection_metadata.update_authority,
+        ctx.accounts.collection_authority.key(),
+        Errors::Unauthorized
+    );
+
+    SetCollectionSizeCpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+        .collection_metadata(&ctx.accounts.collection_metadata.to_account_info())
+        .collection_authority(&ctx.accounts.collection_authority.to_account_info())
+        .collection_mint(&ctx.accounts.collection_mint.to_account_info())
+        .set_collection_size_args(SetCollectionSizeArgs { size })
+        .invoke()?;
+
+    Ok(())
+}
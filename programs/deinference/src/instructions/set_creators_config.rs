@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use crate::{error::Errors, instructions::validate_royalties, metadata::KnowledgeCreator, state::{Config, CreatorsConfig}};
+
+#[event]
+pub struct CreatorsConfigSet {
+    pub creators_config: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct SetCreatorsConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // space = account disc (8) + authority (32) + seller_fee_basis_points (2) + vec prefix (4) + max creators (5 * 34)
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 216,
+        seeds = [b"creators_config", authority.key().as_ref()],
+        bump,
+        constraint = creators_config.authority == Pubkey::default() || creators_config.authority == authority.key() @ Errors::Unauthorized
+    )]
+    pub creators_config: Account<'info, CreatorsConfig>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Creates the authority's creators config, or replaces its contents. Leaves already minted
+// with the config keep the creator hash they were minted with
+pub fn set_creators_config(
+    ctx: Context<SetCreatorsConfig>,
+    seller_fee_basis_points: u16,
+    creators: Vec<KnowledgeCreator>,
+) -> Result<()> {
+    verbose_msg!("set_creators_config: creators_config {} bump {}, seller_fee_basis_points {}, creators {}", ctx.accounts.creators_config.key(), ctx.bumps.creators_config, seller_fee_basis_points, creators.len());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    validate_royalties(seller_fee_basis_points, &creators)?;
+
+    let creators_config = &mut ctx.accounts.creators_config;
+    creators_config.authority = ctx.accounts.authority.key();
+    creators_config.seller_fee_basis_points = seller_fee_basis_points;
+    creators_config.creators = creators;
+
+    emit!(CreatorsConfigSet {
+        creators_config: creators_config.key(),
+        authority: creators_config.authority,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::{instructions::SetDecompressibleStateCpiBuilder, types::DecompressibleState as BubblegumDecompressibleState};
+use crate::{error::Errors, state::{is_valid_role, tree_authority_seeds, Config, TreeRegistry, TREE_OWNER_SEED}, MplBubblegum};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DecompressibleState {
+    Enabled,
+    Disabled,
+}
+
+impl From<DecompressibleState> for BubblegumDecompressibleState {
+    fn from(state: DecompressibleState) -> Self {
+        match state {
+            DecompressibleState::Enabled => BubblegumDecompressibleState::Enabled,
+            DecompressibleState::Disabled => BubblegumDecompressibleState::Disabled,
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(state: DecompressibleState, role: Vec<u8>)]
+pub struct SetDecompressibleState<'info> {
+    // Wallet that created the tree through this program
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry", tree.key().as_ref()],
+        bump,
+        has_one = creator @ Errors::Unauthorized
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    #[account(constraint = is_valid_role(&role) @ Errors::InvalidAuthoritySeeds)]
+    pub tree: UncheckedAccount<'info>,
+
+    // Must be the authority for the role the tree was created with
+    #[account(
+        seeds = [TREE_OWNER_SEED, role.as_ref(), tree.key().as_ref()],
+        bump
+    )]
+    /// CHECK: This account used as a signing PDA only
+    pub tree_owner: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+}
+
+// Bubblegum refuses to redeem leaves of a tree whose decompression is disabled, so leaves of
+// an immutable archive can never become spl tokens
+pub fn set_decompressible_state(
+    ctx: Context<SetDecompressibleState>,
+    state: DecompressibleState,
+    role: Vec<u8>,
+) -> Result<()> {
+    verbose_msg!("set_decompressible_state: tree_owner {} bump {}, tree {}, role {:?}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key(), role);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let tree_key = ctx.accounts.tree.key();
+    let [owner_seed, role_seed, tree_seed] = tree_authority_seeds(&role, &tree_key);
+
+    SetDecompressibleStateCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .tree_creator(&ctx.accounts.tree_owner.to_account_info())
+        .decompressable_state(state.into())
+        .invoke_signed(&[&[
+            owner_seed,
+            role_seed,
+            tree_seed,
+            &[ctx.bumps.tree_owner]
+        ]])?;
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use crate::{error::Errors, state::Config};
+
+#[event]
+pub struct PausedSet {
+    pub paused: bool,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = admin @ Errors::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+}
+
+// While paused, every instruction that changes tree, leaf or task state fails with Paused. Read only
+// instructions, init_config, set_paused and close_account stay available
+pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    verbose_msg!("set_paused: config {} bump {}, paused {}", ctx.accounts.config.key(), ctx.bumps.config, paused);
+    ctx.accounts.config.paused = paused;
+
+    emit!(PausedSet { paused });
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::SetTreeDelegateCpiBuilder;
+use crate::{error::Errors, state::{is_valid_role, tree_authority_seeds, Config, TreeRegistry, TREE_OWNER_SEED}, MplBubblegum};
+
+#[derive(Accounts)]
+#[instruction(role: Vec<u8>)]
+pub struct SetTreeOwner<'info> {
+    // Wallet that created the tree through this program
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry", tree.key().as_ref()],
+        bump,
+        has_one = creator @ Errors::Unauthorized
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: This account is checked in the downstream instruction
+    #[account(constraint = is_valid_role(&role) @ Errors::InvalidAuthoritySeeds)]
+    pub tree: UncheckedAccount<'info>,
+
+    // Must be the authority for the role the tree was created with
+    #[account(
+        seeds = [TREE_OWNER_SEED, role.as_ref(), tree.key().as_ref()],
+        bump
+    )]
+    /// CHECK: This account used as a signing PDA only
+    pub tree_owner: UncheckedAccount<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub new_tree_delegate: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+// The tree_owner pda stays the tree creator, only minting rights are delegated
+pub fn set_tree_owner(ctx: Context<SetTreeOwner>, role: Vec<u8>) -> Result<()> {
+    verbose_msg!("set_tree_owner: tree_owner {} bump {}, tree {}, new_tree_delegate {}, role {:?}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key(), ctx.accounts.new_tree_delegate.key(), role);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let tree_key = ctx.accounts.tree.key();
+    let [owner_seed, role_seed, tree_seed] = tree_authority_seeds(&role, &tree_key);
+
+    SetTreeDelegateCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .tree_creator(&ctx.accounts.tree_owner.to_account_info())
+        .new_tree_delegate(&ctx.accounts.new_tree_delegate.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .invoke_signed(&[&[
+            owner_seed,
+            role_seed,
+            tree_seed,
+            &[ctx.bumps.tree_owner]
+        ]])?;
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use crate::{Noop, SplAccountCompression, error::Errors, compression::init_empty_merkle_tree_ix, merkle_tree::{canopy_depth, merkle_tree_account_size}, instructions::is_valid_tree_config, state::{Config, TreeRegistry, TREE_OWNER_SEED}};
+
+#[derive(Accounts)]
+pub struct CreateLeafTree<'info> {
+    #[account(zero)]
+    /// CHECK: This account is modified in the downstream program
+    pub tree: UncheckedAccount<'info>,
+
+    // Compression authority of the tree, in place of bubblegum's tree config
+    #[account(
+        seeds = [TREE_OWNER_SEED, tree.key().as_ref()],
+        bump
+    )]
+    /// CHECK: This account used as a signing PDA only
+    pub tree_owner: UncheckedAccount<'info>,
+
+    // Becomes the only wallet allowed to append to the tree
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    // space = account disc (8) + creator (32) + max_depth (4) + max_buffer_size (4) + canopy_depth (4) + created_at (8) + num_minted (8) + collection_minted (8) + last_nonce (8) + num_appended (8)
+    #[account(
+        init, payer = creator, space = 92, seeds = [b"registry", tree.key().as_ref()], bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub system_program: Program<'info, System>,
+}
+
+// Creates a tree of raw leaves, managed through spl account compression without bubblegum
+pub fn create_leaf_tree(ctx: Context<CreateLeafTree>, max_depth: u32, max_buffer_size: u32) -> Result<()> {
+    verbose_msg!("create_leaf_tree: tree_owner {} bump {}, tree {}, creator {}, max_depth {}, max_buffer_size {}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key(), ctx.accounts.creator.key(), max_depth, max_buffer_size);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    require!(is_valid_tree_config(max_depth, max_buffer_size), Errors::InvalidTreeConfig);
+    require_gte!(
+        ctx.accounts.tree.data_len(),
+        merkle_tree_account_size(max_depth, max_buffer_size),
+        Errors::TreeAccountTooSmall
+    );
+
+    let ix = init_empty_merkle_tree_ix(
+        ctx.accounts.tree.key(),
+        ctx.accounts.tree_owner.key(),
+        ctx.accounts.log_wrapper.key(),
+        max_depth,
+        max_buffer_size,
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.tree.to_account_info(),
+            ctx.accounts.tree_owner.to_account_info(),
+            ctx.accounts.log_wrapper.to_account_info(),
+        ],
+        &[&[
+            TREE_OWNER_SEED,
+            ctx.accounts.tree.key().as_ref(),
+            &[ctx.bumps.tree_owner],
+        ]],
+    )?;
+
+    let tree_registry = &mut ctx.accounts.tree_registry;
+    tree_registry.creator = ctx.accounts.creator.key();
+    tree_registry.max_depth = max_depth;
+    tree_registry.max_buffer_size = max_buffer_size;
+    tree_registry.canopy_depth = canopy_depth(ctx.accounts.tree.data_len(), max_depth, max_buffer_size)
+        .ok_or(Errors::TreeAccountTooSmall)?;
+    tree_registry.created_at = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
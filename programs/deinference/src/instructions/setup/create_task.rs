@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
-use crate::state::TaskData;
+use crate::error::Errors;
+use crate::state::{Config, TaskData};
 use crate::Metadata;
 
 #[derive(Accounts)]
@@ -16,11 +17,17 @@ pub struct CreateTask<'info> {
 
     #[account(mut)]
     pub payer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
     pub mpl_token_metadata: Program<'info, Metadata>, 
     pub system_program: Program<'info, System>,
 }
 
 pub fn create_task(ctx: Context<CreateTask>) -> Result<()> {
+    verbose_msg!("create_task: task_data {} bump {}, collection_mint {}", ctx.accounts.task_data.key(), ctx.bumps.task_data, ctx.accounts.collection_mint.key());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
     let collection_mint_pk = ctx.accounts.collection_mint.key;
     
     let task_data = &mut ctx.accounts.task_data;
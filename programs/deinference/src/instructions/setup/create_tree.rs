@@ -1,10 +1,51 @@
 use anchor_lang::prelude::*;
-use mpl_bubblegum::instructions::CreateTreeConfigCpiBuilder;
-use crate::{Noop, MplBubblegum, SplAccountCompression, state::ProgramState, state::TreeInfo};
+use mpl_bubblegum::{accounts::TreeConfig, instructions::CreateTreeConfigCpiBuilder, programs::MPL_BUBBLEGUM_ID};
+use crate::{Noop, MplBubblegum, SplAccountCompression, error::Errors, merkle_tree::tree_account_size, state::Config, state::ProgramState, state::TreeInfo, state::TreeRegistry, state::MintAuthority, state::MAX_TREES, state::is_valid_role, state::tree_authority_seeds, state::TREE_OWNER_SEED};
+
+// (max_depth, max_buffer_size) pairs supported by spl account compression
+pub const VALID_TREE_CONFIGS: [(u32, u32); 26] = [
+    (3, 8),
+    (5, 8),
+    (14, 64),
+    (14, 256),
+    (14, 1024),
+    (14, 2048),
+    (15, 64),
+    (16, 64),
+    (17, 64),
+    (18, 64),
+    (19, 64),
+    (20, 64),
+    (20, 256),
+    (20, 1024),
+    (20, 2048),
+    (24, 64),
+    (24, 256),
+    (24, 512),
+    (24, 1024),
+    (24, 2048),
+    (26, 512),
+    (26, 1024),
+    (26, 2048),
+    (30, 512),
+    (30, 1024),
+    (30, 2048),
+];
+
+#[event]
+pub struct TreeCreated {
+    pub tree: Pubkey,
+    pub creator: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub canopy_depth: u32,
+}
 
 #[derive(Accounts)]
+#[instruction(max_depth: u32, max_buffer_size: u32, canopy_depth: u32, role: Vec<u8>)]
 pub struct CreateTree<'info> {
-    #[account(zero)]
+    // Not constrained to zero so a retried creation reaches the handler
+    #[account(mut, constraint = is_valid_role(&role) @ Errors::InvalidAuthoritySeeds)]
     /// CHECK: This account is modified in the downstream program
     pub tree: UncheckedAccount<'info>,
 
@@ -16,8 +57,9 @@ pub struct CreateTree<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
+    // Tree creator, derived for the role. An empty role is the default tree_owner
     #[account(
-        seeds = [b"tree_owner", tree.key().as_ref()],
+        seeds = [TREE_OWNER_SEED, role.as_ref(), tree.key().as_ref()],
         bump
     )]
     /// CHECK: This account used as a signing PDA only
@@ -30,13 +72,62 @@ pub struct CreateTree<'info> {
     )]
     pub program_state: Account<'info, ProgramState>,
 
+    // space = account disc (8) + creator (32) + max_depth (4) + max_buffer_size (4) + canopy_depth (4) + created_at (8) + num_minted (8) + collection_minted (8) + last_nonce (8) + num_appended (8)
+    #[account(
+        init_if_needed, payer = payer, space = 92, seeds = [b"registry", tree.key().as_ref()], bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    // space = account disc (8) + vec size (4) + max_#_minters (16) * pubkey (32)
+    #[account(
+        init_if_needed, payer = payer, space = 524, seeds = [b"mint_auth", tree.key().as_ref()], bump
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
     pub mpl_bubblegum_program: Program<'info, MplBubblegum>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    // Mandatory, bubblegum rejects tree creation without the noop program
     pub log_wrapper: Program<'info, Noop>,
     pub compression_program: Program<'info, SplAccountCompression>,
     pub system_program: Program<'info, System>
 }
 
-pub fn create_tree(ctx: Context<CreateTree>, max_depth: u32, max_buffer_size: u32) -> Result<()> {
+pub fn create_tree(
+    ctx: Context<CreateTree>,
+    max_depth: u32,
+    max_buffer_size: u32,
+    canopy_depth: u32,
+    role: Vec<u8>,
+) -> Result<()> {
+    verbose_msg!("create_tree: tree_owner {} bump {}, tree {}, max_depth {}, max_buffer_size {}, canopy_depth {}, role {:?}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key(), max_depth, max_buffer_size, canopy_depth, role);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    // Retrying the creation of a tree this program already created is a no-op
+    if *ctx.accounts.tree_config.owner == MPL_BUBBLEGUM_ID {
+        let tree_config = TreeConfig::try_from(&ctx.accounts.tree_config.to_account_info())
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        require_keys_eq!(tree_config.tree_creator, ctx.accounts.tree_owner.key(), Errors::AlreadyInitialized);
+        // A registry without a creator was only just created by init_if_needed, so the tree has
+        // none and failing rolls it and the allowlist back, leaving the tree to backfill_registry
+        require_keys_neq!(ctx.accounts.tree_registry.creator, Pubkey::default(), Errors::TreeNotFound);
+        return Ok(());
+    }
+
+    require!(is_valid_tree_config(max_depth, max_buffer_size), Errors::InvalidTreeConfig);
+    require_gte!(max_depth, canopy_depth, Errors::InvalidCanopyDepth);
+
+    // Exact, so the canopy depth recorded in the registry is the canopy the account holds
+    require_eq!(
+        ctx.accounts.tree.data_len(),
+        tree_account_size(max_depth, max_buffer_size, canopy_depth),
+        Errors::TreeAccountSizeMismatch
+    );
+
+    let tree_key = ctx.accounts.tree.key();
+    let [owner_seed, role_seed, tree_seed] = tree_authority_seeds(&role, &tree_key);
+
     CreateTreeConfigCpiBuilder::new(&ctx.accounts.mpl_bubblegum_program)
         .tree_config(&ctx.accounts.tree_config)
         .merkle_tree(&ctx.accounts.tree)
@@ -48,8 +139,9 @@ pub fn create_tree(ctx: Context<CreateTree>, max_depth: u32, max_buffer_size: u3
         .max_depth(max_depth)
         .max_buffer_size(max_buffer_size)
         .invoke_signed(&[&[
-            b"tree_owner",
-            ctx.accounts.tree.key().as_ref(),
+            owner_seed,
+            role_seed,
+            tree_seed,
             &[ctx.bumps.tree_owner]
         ]]
     )?;
@@ -57,6 +149,7 @@ pub fn create_tree(ctx: Context<CreateTree>, max_depth: u32, max_buffer_size: u3
     // Update pda state account:
 
     let program_state = &mut ctx.accounts.program_state;
+    require!(program_state.trees.len() < MAX_TREES, Errors::TooManyTrees);
 
     let tree_address = *ctx.accounts.tree.key;
     let tree_config = *ctx.accounts.tree_config.key;
@@ -70,5 +163,49 @@ pub fn create_tree(ctx: Context<CreateTree>, max_depth: u32, max_buffer_size: u3
     program_state.trees.push(new_tree);
     program_state.tree_count += 1;
 
+    let tree_registry = &mut ctx.accounts.tree_registry;
+    tree_registry.creator = *ctx.accounts.payer.key;
+    tree_registry.max_depth = max_depth;
+    tree_registry.max_buffer_size = max_buffer_size;
+    tree_registry.canopy_depth = canopy_depth;
+    tree_registry.created_at = Clock::get()?.unix_timestamp;
+    tree_registry.num_minted = 0;
+    tree_registry.collection_minted = 0;
+    tree_registry.last_nonce = 0;
+    tree_registry.num_appended = 0;
+
+    // The creator starts as the only allowed minter
+    ctx.accounts.mint_authority.minters = vec![*ctx.accounts.payer.key];
+
+    emit!(TreeCreated {
+        tree: tree_address,
+        creator: *ctx.accounts.payer.key,
+        max_depth,
+        max_buffer_size,
+        canopy_depth,
+    });
+
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn is_valid_tree_config(max_depth: u32, max_buffer_size: u32) -> bool {
+    VALID_TREE_CONFIGS.contains(&(max_depth, max_buffer_size))
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_tree_config() {
+        assert!(is_valid_tree_config(14, 64));
+        assert!(is_valid_tree_config(30, 512));
+    }
+
+    #[test]
+    fn test_invalid_tree_config() {
+        assert!(!is_valid_tree_config(14, 65));
+        assert!(!is_valid_tree_config(31, 512));
+    }
+}
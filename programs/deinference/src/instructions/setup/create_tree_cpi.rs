@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey::{MAX_SEEDS, MAX_SEED_LEN};
+use mpl_bubblegum::instructions::CreateTreeConfigCpiBuilder;
+use crate::{Noop, MplBubblegum, SplAccountCompression, error::Errors, merkle_tree::tree_account_size, state::Config, state::ProgramState, state::TreeInfo, state::TreeRegistry, state::MintAuthority, state::MAX_TREES, state::TREE_OWNER_SEED, instructions::{is_valid_tree_config, TreeCreated}};
+
+#[derive(Accounts)]
+pub struct CreateTreeCpi<'info> {
+    #[account(zero)]
+    /// CHECK: This account is modified in the downstream program
+    pub tree: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    // Pda derived from the merkle tree public key and bubblegum program
+    /// CHECK: This account must be all zeros, initialized by the downstrea program
+    pub tree_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // Pda of the calling program, signed for through invoke_signed
+    pub authority: Signer<'info>,
+
+    /// CHECK: Only used to derive the authority address from the provided seeds
+    #[account(executable)]
+    pub parent_program: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [TREE_OWNER_SEED, tree.key().as_ref()],
+        bump
+    )]
+    /// CHECK: This account used as a signing PDA only
+    pub tree_owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"knowledge"],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    // space = account disc (8) + creator (32) + max_depth (4) + max_buffer_size (4) + canopy_depth (4) + created_at (8) + num_minted (8) + collection_minted (8) + last_nonce (8) + num_appended (8)
+    #[account(
+        init, payer = payer, space = 92, seeds = [b"registry", tree.key().as_ref()], bump
+    )]
+    pub tree_registry: Account<'info, TreeRegistry>,
+
+    // space = account disc (8) + vec size (4) + max_#_minters (16) * pubkey (32)
+    #[account(
+        init, payer = payer, space = 524, seeds = [b"mint_auth", tree.key().as_ref()], bump
+    )]
+    pub mint_authority: Account<'info, MintAuthority>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub mpl_bubblegum_program: Program<'info, MplBubblegum>,
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub system_program: Program<'info, System>
+}
+
+// Called by another program through cpi. The tree is still created by the tree_owner pda, but the
+// calling program's pda is recorded as its creator and only minter, so it gates all later mints
+pub fn create_tree_cpi(
+    ctx: Context<CreateTreeCpi>,
+    max_depth: u32,
+    max_buffer_size: u32,
+    canopy_depth: u32,
+    authority_seeds: Vec<Vec<u8>>,
+) -> Result<()> {
+    verbose_msg!("create_tree_cpi: tree_owner {} bump {}, tree {}, authority {}, parent_program {}, max_depth {}, max_buffer_size {}, canopy_depth {}", ctx.accounts.tree_owner.key(), ctx.bumps.tree_owner, ctx.accounts.tree.key(), ctx.accounts.authority.key(), ctx.accounts.parent_program.key(), max_depth, max_buffer_size, canopy_depth);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    let authority = authority_address(&authority_seeds, ctx.accounts.parent_program.key)?;
+    require_keys_eq!(authority, ctx.accounts.authority.key(), Errors::Unauthorized);
+
+    require!(is_valid_tree_config(max_depth, max_buffer_size), Errors::InvalidTreeConfig);
+    require_gte!(max_depth, canopy_depth, Errors::InvalidCanopyDepth);
+
+    // Exact, so the canopy depth recorded in the registry is the canopy the account holds
+    require_eq!(
+        ctx.accounts.tree.data_len(),
+        tree_account_size(max_depth, max_buffer_size, canopy_depth),
+        Errors::TreeAccountSizeMismatch
+    );
+
+    CreateTreeConfigCpiBuilder::new(&ctx.accounts.mpl_bubblegum_program)
+        .tree_config(&ctx.accounts.tree_config)
+        .merkle_tree(&ctx.accounts.tree)
+        .payer(&ctx.accounts.payer)
+        .tree_creator(&ctx.accounts.tree_owner)
+        .log_wrapper(&ctx.accounts.log_wrapper)
+        .compression_program(&ctx.accounts.compression_program)
+        .system_program(&ctx.accounts.system_program)
+        .max_depth(max_depth)
+        .max_buffer_size(max_buffer_size)
+        .invoke_signed(&[&[
+            TREE_OWNER_SEED,
+            ctx.accounts.tree.key().as_ref(),
+            &[ctx.bumps.tree_owner]
+        ]]
+    )?;
+
+    let program_state = &mut ctx.accounts.program_state;
+    require!(program_state.trees.len() < MAX_TREES, Errors::TooManyTrees);
+
+    program_state.trees.push(TreeInfo {
+        tree_address: *ctx.accounts.tree.key,
+        tree_config: *ctx.accounts.tree_config.key,
+        current_index: 0
+    });
+    program_state.tree_count += 1;
+
+    let tree_registry = &mut ctx.accounts.tree_registry;
+    tree_registry.creator = authority;
+    tree_registry.max_depth = max_depth;
+    tree_registry.max_buffer_size = max_buffer_size;
+    tree_registry.canopy_depth = canopy_depth;
+    tree_registry.created_at = Clock::get()?.unix_timestamp;
+
+    ctx.accounts.mint_authority.minters = vec![authority];
+
+    emit!(TreeCreated {
+        tree: *ctx.accounts.tree.key,
+        creator: authority,
+        max_depth,
+        max_buffer_size,
+        canopy_depth,
+    });
+
+    Ok(())
+}
+
+// Seeds include the bump, and are bounded by the runtime limits on pda derivation
+pub fn authority_address(authority_seeds: &[Vec<u8>], program_id: &Pubkey) -> Result<Pubkey> {
+    require!(
+        !authority_seeds.is_empty() && authority_seeds.len() <= MAX_SEEDS,
+        Errors::InvalidAuthoritySeeds
+    );
+    require!(
+        authority_seeds.iter().all(|seed| seed.len() <= MAX_SEED_LEN),
+        Errors::InvalidAuthoritySeeds
+    );
+
+    let seeds: Vec<&[u8]> = authority_seeds.iter().map(|seed| seed.as_slice()).collect();
+    Pubkey::create_program_address(&seeds, program_id)
+        .map_err(|_| error!(Errors::InvalidAuthoritySeeds))
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authority_address() {
+        let program_id = Pubkey::new_unique();
+        let (pda, bump) = Pubkey::find_program_address(&[b"governance"], &program_id);
+
+        let seeds = vec![b"governance".to_vec(), vec![bump]];
+        assert_eq!(authority_address(&seeds, &program_id).unwrap(), pda);
+    }
+
+    #[test]
+    fn test_authority_address_bounds() {
+        let program_id = Pubkey::new_unique();
+
+        assert!(authority_address(&[], &program_id).unwrap_err() == Errors::InvalidAuthoritySeeds.into());
+
+        let too_many = vec![vec![0u8]; MAX_SEEDS + 1];
+        assert!(authority_address(&too_many, &program_id).unwrap_err() == Errors::InvalidAuthoritySeeds.into());
+
+        let too_long = vec![vec![0u8; MAX_SEED_LEN + 1]];
+        assert!(authority_address(&too_long, &program_id).unwrap_err() == Errors::InvalidAuthoritySeeds.into());
+    }
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::CreateTreeConfigCpiBuilder;
+use crate::{Noop, MplBubblegum, SplAccountCompression, error::Errors, merkle_tree::tree_account_size, state::Config, instructions::is_valid_tree_config};
+
+#[derive(Accounts)]
+pub struct CreateTreeWithCreator<'info> {
+    #[account(zero)]
+    /// CHECK: This account is modified in the downstream program
+    pub tree: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    // Pda derived from the merkle tree public key and bubblegum program
+    /// CHECK: This account must be all zeros, initialized by the downstrea program
+    pub tree_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // External authority that becomes the tree creator in place of the tree_owner pda
+    pub tree_creator: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub mpl_bubblegum_program: Program<'info, MplBubblegum>,
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub system_program: Program<'info, System>
+}
+
+// The tree is not registered with this program, since its pdas have no authority over it.
+// The creator mints and delegates directly through bubblegum
+pub fn create_tree_with_creator(
+    ctx: Context<CreateTreeWithCreator>,
+    max_depth: u32,
+    max_buffer_size: u32,
+    canopy_depth: u32,
+) -> Result<()> {
+    verbose_msg!("create_tree_with_creator: tree {}, tree_creator {}, max_depth {}, max_buffer_size {}, canopy_depth {}", ctx.accounts.tree.key(), ctx.accounts.tree_creator.key(), max_depth, max_buffer_size, canopy_depth);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    require!(is_valid_tree_config(max_depth, max_buffer_size), Errors::InvalidTreeConfig);
+    require_gte!(max_depth, canopy_depth, Errors::InvalidCanopyDepth);
+
+    require_eq!(
+        ctx.accounts.tree.data_len(),
+        tree_account_size(max_depth, max_buffer_size, canopy_depth),
+        Errors::TreeAccountSizeMismatch
+    );
+
+    CreateTreeConfigCpiBuilder::new(&ctx.accounts.mpl_bubblegum_program)
+        .tree_config(&ctx.accounts.tree_config)
+        .merkle_tree(&ctx.accounts.tree)
+        .payer(&ctx.accounts.payer)
+        .tree_creator(&ctx.accounts.tree_creator)
+        .log_wrapper(&ctx.accounts.log_wrapper)
+        .compression_program(&ctx.accounts.compression_program)
+        .system_program(&ctx.accounts.system_program)
+        .max_depth(max_depth)
+        .max_buffer_size(max_buffer_size)
+        .invoke()?;
+
+    Ok(())
+}
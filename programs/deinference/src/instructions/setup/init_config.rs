@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::{error::Errors, state::{Config, ProgramState}};
+
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    // Program authority, becomes the config admin
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"knowledge"],
+        bump,
+        has_one = creator @ Errors::Unauthorized
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    // space = account disc (8) + admin (32) + paused (1)
+    #[account(
+        init, payer = creator, space = 41, seeds = [b"config"], bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_config(ctx: Context<InitConfig>) -> Result<()> {
+    verbose_msg!("init_config: config {} bump {}", ctx.accounts.config.key(), ctx.bumps.config);
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.creator.key();
+    config.paused = false;
+
+    Ok(())
+}
@@ -1,12 +1,12 @@
 use anchor_lang::prelude::*;
 use std::str::FromStr;
-use crate::state::ProgramState;
+use crate::state::{ProgramState, MAX_TREES};
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
-    // space = account disc (8) + pubkey (32) + vec size (4) + tree_count (2) + max_trees (1) * tree info (64)
+    // space = account disc (8) + pubkey (32) + vec size (4) + tree_count (2) + max_trees * tree info (66)
     #[account(
-        init, payer = payer, space = 46 + 1 * 66, seeds = [b"knowledge"], bump
+        init, payer = payer, space = 46 + MAX_TREES * 66, seeds = [b"knowledge"], bump
     )]
     pub program_state: Account<'info, ProgramState>,
 
@@ -17,6 +17,7 @@ pub struct Initialize<'info> {
 }
 
 pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    verbose_msg!("initialize: program_state {} bump {}", ctx.accounts.program_state.key(), ctx.bumps.program_state);
     // Initialize program state account
     let program_state = &mut ctx.accounts.program_state;
     let creator_pbk_str = "2gUrmvYsLTpXB5VwjP2ZpXD4kY4HWRP89aDzQQ7TKbwh";
@@ -1,7 +1,15 @@
-pub mod create_tree;
-pub mod create_task;
-pub mod initialize;
+mod create_tree;
+mod create_task;
+mod initialize;
+mod create_leaf_tree;
+mod create_tree_with_creator;
+mod create_tree_cpi;
+mod init_config;
 
 pub use create_tree::*;
 pub use create_task::*;
-pub use initialize::*;
\ No newline at end of file
+pub use initialize::*;
+pub use create_leaf_tree::*;
+pub use create_tree_with_creator::*;
+pub use create_tree_cpi::*;
+pub use init_config::*;
\ No newline at end of file
@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::state::{InferenceRequest, RequestStatus, ResultEntry, TaskData};
+use crate::state::{Config, InferenceRequest, RequestStatus, ResultEntry, TaskData};
 use crate::error::Errors;
 
 #[derive(Accounts)]
@@ -24,9 +24,13 @@ pub struct SubmitPred<'info> {
     )]
     pub task_data: Account<'info, TaskData>,
 
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
 }
 
 pub fn submit_pred(ctx: Context<SubmitPred>, request_id: u16, weights_hash: [u8; 32], prediction: Vec<u8>) -> Result<()> {
+    verbose_msg!("submit_pred: request_state {} bump {}, request_id {}", ctx.accounts.request_state.key(), ctx.bumps.request_state, request_id);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
     let task_data = &ctx.accounts.task_data;
     let request_state = &mut ctx.accounts.request_state;
     
@@ -41,8 +45,8 @@ pub fn submit_pred(ctx: Context<SubmitPred>, request_id: u16, weights_hash: [u8;
     
 
     let result = ResultEntry {
-        weights_hash: weights_hash,
-        prediction: prediction
+        weights_hash,
+        prediction
     };
 
     request_state.results.push(result);
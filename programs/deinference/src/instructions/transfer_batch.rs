@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_lang::error::ErrorCode;
+use mpl_bubblegum::instructions::TransferCpiBuilder;
+use crate::{error::Errors, merkle_tree::proof_length, state::Config, MplBubblegum, Noop, SplAccountCompression};
+use crate::instructions::{frozen_marker_address, require_not_frozen, LeafTransferred};
+use crate::proof::{is_bubblegum_tree, proof_accounts};
+
+// The runtime locks at most 64 accounts per transaction. The accounts below, this program and the
+// compute budget program take 11, which leaves 53 for the items' frozen markers and proof nodes.
+pub const MAX_TRANSFER_BATCH_ACCOUNTS: usize = 53;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TransferArgs {
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+}
+
+#[derive(Accounts)]
+pub struct TransferBatch<'info> {
+    /// CHECK: This account is checked in the downstream instruction
+    pub tree_config: UncheckedAccount<'info>,
+
+    // Owner of every leaf in the batch, also passed as their delegate
+    pub leaf_owner: Signer<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub new_leaf_owner: UncheckedAccount<'info>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut, constraint = is_bubblegum_tree(&tree, tree_config.key) @ Errors::InvalidTreeAccount)]
+    pub tree: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+// remaining_accounts holds one group per item, in item order: the item's frozen marker pda
+// followed by its proof nodes, ordered from leaf to root. Every leaf is in the same tree, so
+// each group has the same length. Leaves with a delegate other than their owner must be
+// transferred with transfer_knowledge. Any failing item fails the whole transaction.
+// The batch is capped by its remaining accounts rather than an item count, since each item takes
+// proof length + 1 of them: a depth 14 tree without a canopy fits 3 items, one with a canopy of
+// depth 10 fits 10. Each item also adds 108 bytes of arguments, so batches of more than a few
+// items need an address lookup table to stay within the 1232 byte transaction size limit
+pub fn transfer_batch<'info>(
+    ctx: Context<'_, '_, '_, 'info, TransferBatch<'info>>,
+    items: Vec<TransferArgs>,
+) -> Result<()> {
+    verbose_msg!("transfer_batch: tree {}, new_leaf_owner {}, items {}", ctx.accounts.tree.key(), ctx.accounts.new_leaf_owner.key(), items.len());
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    require!(!items.is_empty(), Errors::InvalidBatchSize);
+
+    let proof_len = proof_length(&ctx.accounts.tree.try_borrow_data()?).ok_or(Errors::TreeAccountTooSmall)?;
+    let group_len = proof_len + 1;
+    require!(items.len() * group_len <= MAX_TRANSFER_BATCH_ACCOUNTS, Errors::TransferBatchTooLarge);
+    require_eq!(ctx.remaining_accounts.len(), items.len() * group_len, Errors::InvalidProofLength);
+
+    let tree_key = ctx.accounts.tree.key();
+    let leaf_owner = ctx.accounts.leaf_owner.to_account_info();
+
+    for (item, group) in items.iter().zip(ctx.remaining_accounts.chunks_exact(group_len)) {
+        let (frozen_marker, proof_nodes) = group.split_first().ok_or(Errors::InvalidProofLength)?;
+        require_keys_eq!(frozen_marker.key(), frozen_marker_address(&tree_key, item.nonce), ErrorCode::ConstraintSeeds);
+        require_not_frozen(frozen_marker)?;
+
+        let proof = proof_accounts(proof_nodes);
+
+        TransferCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+            .tree_config(&ctx.accounts.tree_config.to_account_info())
+            .leaf_owner(&leaf_owner, true)
+            .leaf_delegate(&leaf_owner, false)
+            .new_leaf_owner(&ctx.accounts.new_leaf_owner.to_account_info())
+            .merkle_tree(&ctx.accounts.tree.to_account_info())
+            .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
+            .compression_program(&ctx.accounts.compression_program.to_account_info())
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .root(item.root)
+            .data_hash(item.data_hash)
+            .creator_hash(item.creator_hash)
+            .nonce(item.nonce)
+            .index(item.index)
+            .add_remaining_accounts(&proof)
+            .invoke()?;
+
+        emit!(LeafTransferred {
+            tree: tree_key,
+            index: item.index,
+            nonce: item.nonce,
+        });
+    }
+
+    Ok(())
+}
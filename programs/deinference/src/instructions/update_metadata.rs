@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::UpdateMetadataCpiBuilder;
+use mpl_bubblegum::types::UpdateArgs;
+use crate::instructions::{validate_metadata, validate_royalties};
+use crate::{error::Errors, metadata::LeafMetadata, state::Config, MplBubblegum, Noop, SplAccountCompression, Metadata};
+use crate::proof::proof_accounts;
+
+// Only these fields of a leaf may change after minting
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct KnowledgeUpdate {
+    pub name: Option<String>,
+    pub uri: Option<String>,
+    pub seller_fee_basis_points: Option<u16>,
+}
+
+impl From<KnowledgeUpdate> for UpdateArgs {
+    fn from(update: KnowledgeUpdate) -> Self {
+        UpdateArgs {
+            name: update.name,
+            symbol: None,
+            uri: update.uri,
+            creators: None,
+            seller_fee_basis_points: update.seller_fee_basis_points,
+            primary_sale_happened: None,
+            is_mutable: None,
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateKnowledgeMetadata<'info> {
+    /// CHECK: This account is checked in the downstream instruction
+    pub tree_config: UncheckedAccount<'info>,
+
+    // Tree delegate, or the collection authority for leaves in a verified collection
+    pub authority: Signer<'info>,
+
+    /// CHECK: This account is checked in the downstream instruction
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: This account is checked in the downstream instruction
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub leaf_owner: UncheckedAccount<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub leaf_delegate: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut)]
+    pub tree: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+// Proof nodes for the leaf are passed in remaining_accounts, ordered from leaf to root
+pub fn update_metadata<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateKnowledgeMetadata<'info>>,
+    root: [u8; 32],
+    nonce: u64,
+    index: u32,
+    current_metadata: LeafMetadata,
+    update: KnowledgeUpdate,
+) -> Result<()> {
+    verbose_msg!("update_metadata: tree {}, authority {}, nonce {}, index {}", ctx.accounts.tree.key(), ctx.accounts.authority.key(), nonce, index);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    require!(current_metadata.is_mutable, Errors::ImmutableMetadata);
+
+    // Fields left unchanged keep their current, already validated values
+    validate_metadata(
+        update.name.as_deref().unwrap_or(&current_metadata.name),
+        update.uri.as_deref().unwrap_or(&current_metadata.uri),
+    )?;
+    validate_royalties(
+        update.seller_fee_basis_points.unwrap_or(current_metadata.seller_fee_basis_points),
+        &current_metadata.creators,
+    )?;
+
+    let proof = proof_accounts(ctx.remaining_accounts);
+    let collection_mint = ctx.accounts.collection_mint.as_ref().map(|account| account.to_account_info());
+    let collection_metadata = ctx.accounts.collection_metadata.as_ref().map(|account| account.to_account_info());
+
+    UpdateMetadataCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .authority(&ctx.accounts.authority.to_account_info())
+        .collection_mint(collection_mint.as_ref())
+        .collection_metadata(collection_metadata.as_ref())
+        .leaf_owner(&ctx.accounts.leaf_owner.to_account_info())
+        .leaf_delegate(&ctx.accounts.leaf_delegate.to_account_info())
+        .payer(&ctx.accounts.payer.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
+        .compression_program(&ctx.accounts.compression_program.to_account_info())
+        .token_metadata_program(&ctx.accounts.token_metadata_program.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .root(root)
+        .nonce(nonce)
+        .index(index)
+        .current_metadata(current_metadata.into())
+        .update_args(update.into())
+        .add_remaining_accounts(&proof)
+        .invoke()?;
+
+    Ok(())
+}
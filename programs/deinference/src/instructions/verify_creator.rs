@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::{UnverifyCreatorCpiBuilder, VerifyCreatorCpiBuilder};
+use crate::{error::Errors, metadata::LeafMetadata, state::Config, MplBubblegum, Noop, SplAccountCompression};
+use crate::proof::proof_accounts;
+
+// Shared by verify_creator and unverify_creator
+#[derive(Accounts)]
+pub struct CreatorVerification<'info> {
+    /// CHECK: This account is checked in the downstream instruction
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub leaf_owner: UncheckedAccount<'info>,
+
+    /// CHECK: This account is neither written to nor read from.
+    pub leaf_delegate: UncheckedAccount<'info>,
+
+    /// CHECK: This account is modified in the downstream program
+    #[account(mut)]
+    pub tree: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub creator: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn verify_creator<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreatorVerification<'info>>,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+    metadata: LeafMetadata,
+) -> Result<()> {
+    verbose_msg!("verify_creator: tree {}, creator {}, nonce {}, index {}", ctx.accounts.tree.key(), ctx.accounts.creator.key(), nonce, index);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    require_creator(&metadata, ctx.accounts.creator.key)?;
+
+    let proof = proof_accounts(ctx.remaining_accounts);
+
+    VerifyCreatorCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .leaf_owner(&ctx.accounts.leaf_owner.to_account_info())
+        .leaf_delegate(&ctx.accounts.leaf_delegate.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .payer(&ctx.accounts.payer.to_account_info())
+        .creator(&ctx.accounts.creator.to_account_info())
+        .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
+        .compression_program(&ctx.accounts.compression_program.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .root(root)
+        .data_hash(data_hash)
+        .creator_hash(creator_hash)
+        .nonce(nonce)
+        .index(index)
+        .metadata(metadata.into())
+        .add_remaining_accounts(&proof)
+        .invoke()?;
+
+    Ok(())
+}
+
+pub fn unverify_creator<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreatorVerification<'info>>,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+    metadata: LeafMetadata,
+) -> Result<()> {
+    verbose_msg!("unverify_creator: tree {}, creator {}, nonce {}, index {}", ctx.accounts.tree.key(), ctx.accounts.creator.key(), nonce, index);
+    require!(!ctx.accounts.config.paused, Errors::Paused);
+    require_creator(&metadata, ctx.accounts.creator.key)?;
+
+    let proof = proof_accounts(ctx.remaining_accounts);
+
+    UnverifyCreatorCpiBuilder::new(&ctx.accounts.bubblegum_program.to_account_info())
+        .tree_config(&ctx.accounts.tree_config.to_account_info())
+        .leaf_owner(&ctx.accounts.leaf_owner.to_account_info())
+        .leaf_delegate(&ctx.accounts.leaf_delegate.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .payer(&ctx.accounts.payer.to_account_info())
+        .creator(&ctx.accounts.creator.to_account_info())
+        .log_wrapper(&ctx.accounts.log_wrapper.to_account_info())
+        .compression_program(&ctx.accounts.compression_program.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .root(root)
+        .data_hash(data_hash)
+        .creator_hash(creator_hash)
+        .nonce(nonce)
+        .index(index)
+        .metadata(metadata.into())
+        .add_remaining_accounts(&proof)
+        .invoke()?;
+
+    Ok(())
+}
+
+fn require_creator(metadata: &LeafMetadata, creator: &Pubkey) -> Result<()> {
+    require!(
+        metadata.creators.iter().any(|entry| &entry.address == creator),
+        Errors::CreatorNotFound
+    );
+    Ok(())
+}
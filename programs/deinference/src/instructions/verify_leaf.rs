@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::VerifyLeafCpiBuilder;
+use crate::SplAccountCompression;
+use crate::proof::proof_accounts;
+
+#[derive(Accounts)]
+pub struct VerifyLeaf<'info> {
+    /// CHECK: This account is checked in the downstream instruction
+    pub tree: UncheckedAccount<'info>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+}
+
+// Fails with the compression program's error unless the proof in remaining_accounts,
+// ordered from leaf to root, proves the leaf at index against root
+pub fn verify_leaf<'info>(
+    ctx: Context<'_, '_, '_, 'info, VerifyLeaf<'info>>,
+    root: [u8; 32],
+    leaf: [u8; 32],
+    index: u32,
+) -> Result<()> {
+    verbose_msg!("verify_leaf: tree {}, index {}", ctx.accounts.tree.key(), index);
+    let proof = proof_accounts(ctx.remaining_accounts);
+
+    VerifyLeafCpiBuilder::new(&ctx.accounts.compression_program.to_account_info())
+        .merkle_tree(&ctx.accounts.tree.to_account_info())
+        .root(root)
+        .leaf(leaf)
+        .index(index)
+        .add_remaining_accounts(&proof)
+        .invoke()?;
+
+    Ok(())
+}
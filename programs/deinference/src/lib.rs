@@ -1,15 +1,56 @@
+// Instruction arguments mirror bubblegum's, so some handlers and the generated cpi
+// functions take more than clippy's default limit
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
 use mpl_bubblegum::programs::{MPL_BUBBLEGUM_ID, SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
 use mpl_token_metadata::programs::MPL_TOKEN_METADATA_ID;
 use crate::instructions::*;
-use crate::instructions::setup::*;
+use crate::metadata::*;
+
+// Logs handler inputs for debugging cpi failures. Compiled out unless the verbose-logs
+// feature is enabled, so release builds pay no compute for it. The disabled branch still
+// type checks the arguments, so values only logged are not reported as unused
+macro_rules! verbose_msg {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "verbose-logs")]
+        anchor_lang::prelude::msg!($($arg)*);
+        #[cfg(not(feature = "verbose-logs"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
 mod verify;
+mod proof;
+mod merkle_tree;
+mod compression;
+mod metadata;
 mod instructions;
 mod state;
 mod error;
 
+pub use error::Errors;
+pub use state::{tree_authority_seeds, TREE_OWNER_SEED};
+
 declare_id!("GJFXHDjc5uCcjQcX2aovmPvD73igW5gvYQSMSh2nbhGd");
 
+// Default tree_owner pda and bump of a tree, as signed by this program's instructions.
+// Exported so cpi clients do not need to repeat the seeds
+pub fn tree_owner_pda(tree: &Pubkey) -> (Pubkey, u8) {
+    tree_authority_pda(&[], tree)
+}
+
+// Tree authority pda and bump for a role, the default tree_owner pda for an empty role
+pub fn tree_authority_pda(role: &[u8], tree: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&tree_authority_seeds(role, tree), &ID)
+}
+
+// The spl noop program. Bubblegum and spl account compression both require it for every tree
+// instruction, since changelogs are only emitted through noop cpis and indexers rebuild leaves
+// and proofs from them. A tree cannot be created or modified without it, so there is no
+// log-free variant of the tree instructions and each one takes it as a checked Program account
 #[derive(Clone)]
 pub struct Noop;
 
@@ -51,28 +92,323 @@ pub mod knowledge_manager {
     use super::*;
 
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        instructions::setup::initialize(ctx)?;
+        instructions::initialize(ctx)?;
+        Ok(())
+    }
+
+    pub fn create_tree(
+        ctx: Context<CreateTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+        canopy_depth: u32,
+        role: Vec<u8>,
+    ) -> Result<()> {
+        instructions::create_tree(ctx, max_depth, max_buffer_size, canopy_depth, role)?;
+        Ok(())
+    }
+
+    pub fn create_tree_with_creator(
+        ctx: Context<CreateTreeWithCreator>,
+        max_depth: u32,
+        max_buffer_size: u32,
+        canopy_depth: u32,
+    ) -> Result<()> {
+        instructions::create_tree_with_creator(ctx, max_depth, max_buffer_size, canopy_depth)?;
+        Ok(())
+    }
+
+    pub fn mint_knowledge(
+        ctx: Context<MintKnowledge>,
+        name: String,
+        symbol: String,
+        uri: String,
+        seller_fee_basis_points: u16,
+        creators: Vec<KnowledgeCreator>,
+        role: Vec<u8>,
+        content_hash: Option<[u8; 32]>,
+        is_mutable: bool,
+    ) -> Result<()> {
+        instructions::mint_knowledge(ctx, name, symbol, uri, seller_fee_basis_points, creators, role, content_hash, is_mutable)?;
+        Ok(())
+    }
+
+    pub fn transfer_knowledge<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferKnowledge<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+        max_root_age_slots: u64,
+    ) -> Result<()> {
+        instructions::transfer_knowledge(ctx, root, data_hash, creator_hash, nonce, index, max_root_age_slots)?;
+        Ok(())
+    }
+
+    pub fn burn_knowledge<'info>(
+        ctx: Context<'_, '_, '_, 'info, BurnKnowledge<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+    ) -> Result<()> {
+        instructions::burn_knowledge(ctx, root, data_hash, creator_hash, nonce, index)?;
+        Ok(())
+    }
+
+    pub fn delegate_knowledge<'info>(
+        ctx: Context<'_, '_, '_, 'info, DelegateKnowledge<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+    ) -> Result<()> {
+        instructions::delegate_knowledge(ctx, root, data_hash, creator_hash, nonce, index)?;
         Ok(())
     }
 
-    pub fn create_tree(ctx: Context<CreateTree>, max_depth: u32, max_buffer_size: u32) -> Result<()> {
-        instructions::setup::create_tree(ctx, max_depth, max_buffer_size)?;
+    pub fn set_tree_owner(ctx: Context<SetTreeOwner>, role: Vec<u8>) -> Result<()> {
+        instructions::set_tree_owner(ctx, role)?;
         Ok(())
     }
 
-    pub fn mint(
-        ctx: Context<Mint>,
+    pub fn mint_to_collection(
+        ctx: Context<MintToCollection>,
         name: String,
         symbol: String,
         uri: String,
         seller_fee_basis_points: u16,
+        role: Vec<u8>,
+    ) -> Result<()> {
+        instructions::mint_to_collection(ctx, name, symbol, uri, seller_fee_basis_points, role)?;
+        Ok(())
+    }
+
+    pub fn mint_batch(ctx: Context<MintBatch>, items: Vec<KnowledgeMetadata>, role: Vec<u8>) -> Result<()> {
+        instructions::mint_batch(ctx, items, role)?;
+        Ok(())
+    }
+
+    pub fn redeem<'info>(
+        ctx: Context<'_, '_, '_, 'info, Redeem<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+    ) -> Result<()> {
+        instructions::redeem(ctx, root, data_hash, creator_hash, nonce, index)?;
+        Ok(())
+    }
+
+    pub fn cancel_redeem<'info>(ctx: Context<'_, '_, '_, 'info, CancelRedeem<'info>>, root: [u8; 32]) -> Result<()> {
+        instructions::cancel_redeem(ctx, root)?;
+        Ok(())
+    }
+
+    pub fn decompress_v1(ctx: Context<DecompressV1>, metadata: LeafMetadata) -> Result<()> {
+        instructions::decompress_v1(ctx, metadata)?;
+        Ok(())
+    }
+
+    pub fn verify_creator<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreatorVerification<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+        metadata: LeafMetadata,
+    ) -> Result<()> {
+        instructions::verify_creator(ctx, root, data_hash, creator_hash, nonce, index, metadata)?;
+        Ok(())
+    }
+
+    pub fn unverify_creator<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreatorVerification<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+        metadata: LeafMetadata,
+    ) -> Result<()> {
+        instructions::unverify_creator(ctx, root, data_hash, creator_hash, nonce, index, metadata)?;
+        Ok(())
+    }
+
+    pub fn update_metadata<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateKnowledgeMetadata<'info>>,
+        root: [u8; 32],
+        nonce: u64,
+        index: u32,
+        current_metadata: LeafMetadata,
+        update: KnowledgeUpdate,
+    ) -> Result<()> {
+        instructions::update_metadata(ctx, root, nonce, index, current_metadata, update)?;
+        Ok(())
+    }
+
+    pub fn verify_leaf<'info>(
+        ctx: Context<'_, '_, '_, 'info, VerifyLeaf<'info>>,
+        root: [u8; 32],
+        leaf: [u8; 32],
+        index: u32,
+    ) -> Result<()> {
+        instructions::verify_leaf(ctx, root, leaf, index)?;
+        Ok(())
+    }
+
+    pub fn create_leaf_tree(ctx: Context<CreateLeafTree>, max_depth: u32, max_buffer_size: u32) -> Result<()> {
+        instructions::create_leaf_tree(ctx, max_depth, max_buffer_size)?;
+        Ok(())
+    }
+
+    pub fn append_leaf(ctx: Context<AppendLeaf>, leaf: [u8; 32]) -> Result<()> {
+        instructions::append_leaf(ctx, leaf)?;
+        Ok(())
+    }
+
+    pub fn append_and_check(ctx: Context<AppendLeaf>, leaf: [u8; 32], expected_new_root: [u8; 32]) -> Result<()> {
+        instructions::append_and_check(ctx, leaf, expected_new_root)?;
+        Ok(())
+    }
+
+    pub fn close_tree(ctx: Context<CloseTree>, role: Vec<u8>) -> Result<()> {
+        instructions::close_tree(ctx, role)?;
+        Ok(())
+    }
+
+    pub fn add_minter(ctx: Context<UpdateMinters>, minter: Pubkey) -> Result<()> {
+        instructions::add_minter(ctx, minter)?;
+        Ok(())
+    }
+
+    pub fn remove_minter(ctx: Context<UpdateMinters>, minter: Pubkey) -> Result<()> {
+        instructions::remove_minter(ctx, minter)?;
+        Ok(())
+    }
+
+    pub fn freeze_leaf(ctx: Context<FreezeLeaf>, nonce: u64) -> Result<()> {
+        instructions::freeze_leaf(ctx, nonce)?;
+        Ok(())
+    }
+
+    pub fn thaw_leaf(ctx: Context<ThawLeaf>, nonce: u64) -> Result<()> {
+        instructions::thaw_leaf(ctx, nonce)?;
+        Ok(())
+    }
+
+    pub fn set_collection_size(ctx: Context<SetCollectionSize>, size: u64) -> Result<()> {
+        instructions::set_collection_size(ctx, size)?;
+        Ok(())
+    }
+
+    pub fn transfer_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferBatch<'info>>,
+        items: Vec<TransferArgs>,
+    ) -> Result<()> {
+        instructions::transfer_batch(ctx, items)?;
+        Ok(())
+    }
+
+    pub fn get_tree_authority(ctx: Context<GetTreeAuthority>, role: Vec<u8>) -> Result<TreeAuthority> {
+        instructions::get_tree_authority(ctx, role)
+    }
+
+    pub fn create_tree_cpi(
+        ctx: Context<CreateTreeCpi>,
+        max_depth: u32,
+        max_buffer_size: u32,
+        canopy_depth: u32,
+        authority_seeds: Vec<Vec<u8>>,
+    ) -> Result<()> {
+        instructions::create_tree_cpi(ctx, max_depth, max_buffer_size, canopy_depth, authority_seeds)?;
+        Ok(())
+    }
+
+    pub fn migrate_tree_owner(ctx: Context<MigrateTreeOwner>) -> Result<()> {
+        instructions::migrate_tree_owner(ctx)?;
+        Ok(())
+    }
+
+    pub fn register_content(ctx: Context<RegisterContent>, hash: [u8; 32], uri: String) -> Result<()> {
+        instructions::register_content(ctx, hash, uri)?;
+        Ok(())
+    }
+
+    pub fn remaining_capacity(ctx: Context<RemainingCapacity>) -> Result<u64> {
+        instructions::remaining_capacity(ctx)
+    }
+
+    pub fn backfill_registry(
+        ctx: Context<BackfillRegistry>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        instructions::backfill_registry(ctx, max_depth, max_buffer_size)?;
+        Ok(())
+    }
+
+    pub fn set_decompressible_state(
+        ctx: Context<SetDecompressibleState>,
+        state: DecompressibleState,
+        role: Vec<u8>,
     ) -> Result<()> {
-        instructions::mint(ctx, name, symbol, uri, seller_fee_basis_points)?;
+        instructions::set_decompressible_state(ctx, state, role)?;
+        Ok(())
+    }
+
+    pub fn require_collection_membership<'info>(
+        ctx: Context<'_, '_, '_, 'info, RequireCollectionMembership<'info>>,
+        root: [u8; 32],
+        nonce: u64,
+        index: u32,
+        metadata: LeafMetadata,
+        collection_mint: Pubkey,
+    ) -> Result<()> {
+        instructions::require_collection_membership(ctx, root, nonce, index, metadata, collection_mint)?;
+        Ok(())
+    }
+
+    pub fn propose_tree_owner(ctx: Context<ProposeTreeOwner>) -> Result<()> {
+        instructions::propose_tree_owner(ctx)?;
+        Ok(())
+    }
+
+    pub fn accept_tree_owner(ctx: Context<AcceptTreeOwner>, role: Vec<u8>) -> Result<()> {
+        instructions::accept_tree_owner(ctx, role)?;
+        Ok(())
+    }
+
+    pub fn cancel_tree_owner(ctx: Context<CancelTreeOwner>) -> Result<()> {
+        instructions::cancel_tree_owner(ctx)?;
+        Ok(())
+    }
+
+    pub fn set_creators_config(ctx: Context<SetCreatorsConfig>, seller_fee_basis_points: u16, creators: Vec<KnowledgeCreator>) -> Result<()> {
+        instructions::set_creators_config(ctx, seller_fee_basis_points, creators)?;
+        Ok(())
+    }
+
+    pub fn compute_leaf_hashes(ctx: Context<ComputeLeafHashes>, metadata: LeafMetadata, nonce: u64, owner: Pubkey, delegate: Pubkey) -> Result<LeafHashes> {
+        instructions::compute_leaf_hashes(ctx, metadata, nonce, owner, delegate)
+    }
+
+    pub fn init_config(ctx: Context<InitConfig>) -> Result<()> {
+        instructions::init_config(ctx)?;
+        Ok(())
+    }
+
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        instructions::set_paused(ctx, paused)?;
         Ok(())
     }
 
     pub fn create_task(ctx: Context<CreateTask>) -> Result<()> {
-        instructions::setup::create_task(ctx)?;
+        instructions::create_task(ctx)?;
         Ok(())
     }
 
@@ -114,3 +450,18 @@ pub mod knowledge_manager {
     }
 }
 
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_owner_pda() {
+        let tree = Pubkey::new_unique();
+        let (tree_owner, bump) = tree_owner_pda(&tree);
+
+        // Same seeds the instructions pass to invoke_signed
+        let signer = Pubkey::create_program_address(&[TREE_OWNER_SEED, tree.as_ref(), &[bump]], &ID).unwrap();
+        assert_eq!(tree_owner, signer);
+    }
+}
@@ -0,0 +1,250 @@
+// Layout of an spl account compression ConcurrentMerkleTree account
+
+// account type (1) + header version (1) + max_buffer_size (4) + max_depth (4) + authority (32) + creation_slot (8) + padding (6)
+pub const MERKLE_TREE_HEADER_SIZE: usize = 56;
+
+const NODE_SIZE: usize = 32;
+
+// sequence_number (8) + active_index (8) + buffer_size (8)
+const TREE_METADATA_SIZE: usize = 24;
+
+// root (32) + path_nodes (32 * depth) + index (4) + padding (4)
+fn change_log_size(max_depth: usize) -> usize {
+    NODE_SIZE + NODE_SIZE * max_depth + 8
+}
+
+// proof (32 * depth) + leaf (32) + index (4) + padding (4)
+fn path_size(max_depth: usize) -> usize {
+    NODE_SIZE * max_depth + NODE_SIZE + 8
+}
+
+// Size of the tree account without a canopy
+pub fn merkle_tree_account_size(max_depth: u32, max_buffer_size: u32) -> usize {
+    let max_depth = max_depth as usize;
+    MERKLE_TREE_HEADER_SIZE
+        + TREE_METADATA_SIZE
+        + max_buffer_size as usize * change_log_size(max_depth)
+        + path_size(max_depth)
+}
+
+// Bytes of a canopy caching the top canopy_depth levels of the tree, excluding the root
+pub fn canopy_size(canopy_depth: u32) -> usize {
+    ((1usize << (canopy_depth + 1)) - 2) * NODE_SIZE
+}
+
+// Size a tree account must be allocated with to hold the tree and a canopy of canopy_depth
+pub fn tree_account_size(max_depth: u32, max_buffer_size: u32, canopy_depth: u32) -> usize {
+    merkle_tree_account_size(max_depth, max_buffer_size) + canopy_size(canopy_depth)
+}
+
+// Reads (max_depth, max_buffer_size) from the tree header, after account type (1) and header version (1)
+pub fn tree_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.len() < MERKLE_TREE_HEADER_SIZE {
+        return None;
+    }
+    let max_buffer_size = u32::from_le_bytes(data[2..6].try_into().ok()?);
+    let max_depth = u32::from_le_bytes(data[6..10].try_into().ok()?);
+    Some((max_depth, max_buffer_size))
+}
+
+// Reads the tree authority from the header, after max_depth
+pub fn tree_authority(data: &[u8]) -> Option<[u8; 32]> {
+    data.get(10..42)?.try_into().ok()
+}
+
+// Depth of the canopy stored after the tree, a canopy of depth c holds 2^(c+1) - 2 nodes
+pub fn canopy_depth(data_len: usize, max_depth: u32, max_buffer_size: u32) -> Option<u32> {
+    let canopy_bytes = data_len.checked_sub(merkle_tree_account_size(max_depth, max_buffer_size))?;
+    Some((canopy_bytes / NODE_SIZE + 2).ilog2() - 1)
+}
+
+// Number of proof nodes a client must pass for a leaf, the rest are read from the canopy
+pub fn proof_length(data: &[u8]) -> Option<usize> {
+    let (max_depth, max_buffer_size) = tree_dimensions(data)?;
+    let canopy_depth = canopy_depth(data.len(), max_depth, max_buffer_size)?;
+    Some(max_depth.saturating_sub(canopy_depth) as usize)
+}
+
+// Offset of the rightmost path's leaf, after the header, tree metadata, changelogs and proof
+fn rightmost_leaf_offset(data: &[u8]) -> Option<usize> {
+    let (max_depth, max_buffer_size) = tree_dimensions(data)?;
+    let max_depth = max_depth as usize;
+    Some(MERKLE_TREE_HEADER_SIZE
+        + TREE_METADATA_SIZE
+        + max_buffer_size as usize * change_log_size(max_depth)
+        + NODE_SIZE * max_depth)
+}
+
+// Number of leaves appended to the tree, stored in the rightmost path's index field
+pub fn appended_leaf_count(data: &[u8]) -> Option<u32> {
+    let index_offset = rightmost_leaf_offset(data)? + NODE_SIZE;
+    Some(u32::from_le_bytes(data.get(index_offset..index_offset + 4)?.try_into().ok()?))
+}
+
+// Index and hash of the most recently appended leaf, read from the tree's rightmost path.
+// The path's index field counts appended leaves, so the last leaf sits one below it
+pub fn last_appended_leaf(data: &[u8]) -> Option<(u32, [u8; 32])> {
+    let leaf_offset = rightmost_leaf_offset(data)?;
+    let leaf: [u8; 32] = data.get(leaf_offset..leaf_offset + NODE_SIZE)?.try_into().ok()?;
+    Some((appended_leaf_count(data)?.checked_sub(1)?, leaf))
+}
+
+// Root after the most recent update, stored in the changelog at the active index
+pub fn current_root(data: &[u8]) -> Option<[u8; 32]> {
+    root_at_age(data, 0)
+}
+
+// Number of updates since root was the tree's root, found by searching the changelog buffer
+// backwards from the active index. Roots that dropped out of the buffer are not found
+pub fn root_age(data: &[u8], root: &[u8; 32]) -> Option<u64> {
+    let (_, max_buffer_size) = tree_dimensions(data)?;
+    let buffer_size = changelog_metadata(data)?.1;
+    (0..buffer_size.min(max_buffer_size as u64)).find(|age| root_at_age(data, *age).as_ref() == Some(root))
+}
+
+// (active_index, buffer_size) of the changelog buffer, from the tree metadata after the header
+fn changelog_metadata(data: &[u8]) -> Option<(u64, u64)> {
+    let metadata = data.get(MERKLE_TREE_HEADER_SIZE..MERKLE_TREE_HEADER_SIZE + TREE_METADATA_SIZE)?;
+    let active_index = u64::from_le_bytes(metadata[8..16].try_into().ok()?);
+    let buffer_size = u64::from_le_bytes(metadata[16..24].try_into().ok()?);
+    Some((active_index, buffer_size))
+}
+
+// Root stored age updates before the active changelog entry
+fn root_at_age(data: &[u8], age: u64) -> Option<[u8; 32]> {
+    let (max_depth, max_buffer_size) = tree_dimensions(data)?;
+    let (active_index, _) = changelog_metadata(data)?;
+    let max_buffer_size = max_buffer_size as u64;
+    let index = (active_index + max_buffer_size).checked_sub(age)?.checked_rem(max_buffer_size)?;
+    let offset = MERKLE_TREE_HEADER_SIZE + TREE_METADATA_SIZE + index as usize * change_log_size(max_depth as usize);
+    data.get(offset..offset + NODE_SIZE)?.try_into().ok()
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::VALID_TREE_CONFIGS;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_merkle_tree_account_size() {
+        // Known sizes from @solana/spl-account-compression getConcurrentMerkleTreeAccountSize
+        assert_eq!(merkle_tree_account_size(3, 8), 1304);
+        assert_eq!(merkle_tree_account_size(14, 64), 31800);
+    }
+
+    #[test]
+    fn test_canopy_size() {
+        assert_eq!(canopy_size(0), 0);
+        assert_eq!(canopy_size(1), 64);
+        assert_eq!(canopy_size(10), 2046 * 32);
+    }
+
+    fn tree_data(max_depth: u32, max_buffer_size: u32, canopy_depth: u32) -> Vec<u8> {
+        let mut data = vec![0; merkle_tree_account_size(max_depth, max_buffer_size) + canopy_size(canopy_depth)];
+        data[2..6].copy_from_slice(&max_buffer_size.to_le_bytes());
+        data[6..10].copy_from_slice(&max_depth.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_proof_length() {
+        assert_eq!(proof_length(&tree_data(3, 8, 0)), Some(3));
+        assert_eq!(proof_length(&tree_data(14, 64, 10)), Some(4));
+        assert_eq!(proof_length(&tree_data(14, 64, 14)), Some(0));
+
+        // Truncated accounts have no valid layout
+        assert_eq!(proof_length(&[0; 10]), None);
+        assert_eq!(proof_length(&tree_data(14, 64, 0)[..1000]), None);
+    }
+
+    #[test]
+    fn test_tree_authority() {
+        let mut data = tree_data(3, 8, 0);
+        data[10..42].copy_from_slice(&[7; 32]);
+        assert_eq!(tree_authority(&data), Some([7; 32]));
+        assert_eq!(tree_dimensions(&data), Some((3, 8)), "Authority sits after the dimensions");
+        assert_eq!(tree_authority(&[0; 20]), None);
+    }
+
+    #[test]
+    fn test_last_appended_leaf() {
+        let mut data = tree_data(3, 8, 0);
+        assert_eq!(last_appended_leaf(&data), None, "Empty tree has no leaves");
+
+        // Rightmost path starts after the header, tree metadata, changelogs and proof
+        let leaf_offset = MERKLE_TREE_HEADER_SIZE + TREE_METADATA_SIZE + 8 * change_log_size(3) + NODE_SIZE * 3;
+        data[leaf_offset..leaf_offset + 32].copy_from_slice(&[9; 32]);
+        data[leaf_offset + 32..leaf_offset + 36].copy_from_slice(&5u32.to_le_bytes());
+        assert_eq!(last_appended_leaf(&data), Some((4, [9; 32])));
+        assert_eq!(appended_leaf_count(&data), Some(5));
+    }
+
+    #[test]
+    fn test_root_age() {
+        let mut data = tree_data(3, 8, 0);
+        let changelogs_offset = MERKLE_TREE_HEADER_SIZE + TREE_METADATA_SIZE;
+        for (index, root) in [[1; 32], [2; 32], [3; 32]].iter().enumerate() {
+            let offset = changelogs_offset + index * change_log_size(3);
+            data[offset..offset + 32].copy_from_slice(root);
+        }
+
+        // Three updates, the latest at active index 2
+        let metadata = MERKLE_TREE_HEADER_SIZE;
+        data[metadata + 8..metadata + 16].copy_from_slice(&2u64.to_le_bytes());
+        data[metadata + 16..metadata + 24].copy_from_slice(&3u64.to_le_bytes());
+
+        assert_eq!(current_root(&data), Some([3; 32]));
+        assert_eq!(root_age(&data, &[3; 32]), Some(0));
+        assert_eq!(root_age(&data, &[1; 32]), Some(2));
+        assert_eq!(root_age(&data, &[4; 32]), None);
+    }
+
+    #[test]
+    fn test_root_age_wraps_around_buffer() {
+        let mut data = tree_data(3, 8, 0);
+        let changelogs_offset = MERKLE_TREE_HEADER_SIZE + TREE_METADATA_SIZE;
+        let offset = changelogs_offset + 7 * change_log_size(3);
+        data[offset..offset + 32].copy_from_slice(&[7; 32]);
+
+        // A full buffer whose latest entry wrapped to index 1
+        let metadata = MERKLE_TREE_HEADER_SIZE;
+        data[metadata + 8..metadata + 16].copy_from_slice(&1u64.to_le_bytes());
+        data[metadata + 16..metadata + 24].copy_from_slice(&8u64.to_le_bytes());
+
+        assert_eq!(root_age(&data, &[7; 32]), Some(2));
+    }
+
+    // Size of a ConcurrentMerkleTreeAccount as spl-account-compression lays it out, written out
+    // independently of the helpers above
+    fn spl_account_size(max_depth: usize, max_buffer_size: usize, canopy_depth: usize) -> usize {
+        let header = 2 + 4 + 4 + 32 + 8 + 6;
+        let tree_metadata = 8 + 8 + 8;
+        let change_log = 32 + 32 * max_depth + 4 + 4;
+        let rightmost_path = 32 * max_depth + 32 + 4 + 4;
+        let canopy = ((1 << (canopy_depth + 1)) - 2) * 32;
+        header + tree_metadata + max_buffer_size * change_log + rightmost_path + canopy
+    }
+
+    fn tree_shape() -> impl Strategy<Value = (u32, u32, u32)> {
+        proptest::sample::select(VALID_TREE_CONFIGS.to_vec())
+            .prop_flat_map(|(max_depth, max_buffer_size)| {
+                (Just(max_depth), Just(max_buffer_size), 0..=max_depth.min(17))
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_tree_account_size_matches_spl_layout((max_depth, max_buffer_size, canopy) in tree_shape()) {
+            let size = tree_account_size(max_depth, max_buffer_size, canopy);
+            prop_assert_eq!(size, spl_account_size(max_depth as usize, max_buffer_size as usize, canopy as usize));
+
+            // The canopy and proof length read back from an account of that size
+            let data = tree_data(max_depth, max_buffer_size, canopy);
+            prop_assert_eq!(data.len(), size);
+            prop_assert_eq!(canopy_depth(size, max_depth, max_buffer_size), Some(canopy));
+            prop_assert_eq!(proof_length(&data), Some((max_depth - canopy) as usize));
+        }
+    }
+}
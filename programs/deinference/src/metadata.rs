@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::{hash::{hash_creators, hash_metadata}, types::{Collection, Creator, LeafSchema, MetadataArgs, TokenProgramVersion, TokenStandard}, utils::get_asset_id};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct KnowledgeCreator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,       // percentage of royalties
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct KnowledgeCollection {
+    pub verified: bool,
+    pub key: Pubkey,     // collection mint
+}
+
+// Full metadata of an existing leaf, needed by instructions that recompute its data hash.
+// Token standard, program version and uses are fixed for leaves minted by this program
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LeafMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub collection: Option<KnowledgeCollection>,
+    pub creators: Vec<KnowledgeCreator>,
+}
+
+impl From<KnowledgeCreator> for Creator {
+    fn from(creator: KnowledgeCreator) -> Self {
+        Creator {
+            address: creator.address,
+            verified: creator.verified,
+            share: creator.share,
+        }
+    }
+}
+
+impl From<LeafMetadata> for MetadataArgs {
+    fn from(metadata: LeafMetadata) -> Self {
+        MetadataArgs {
+            name: metadata.name,
+            symbol: metadata.symbol,
+            uri: metadata.uri,
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            primary_sale_happened: metadata.primary_sale_happened,
+            is_mutable: metadata.is_mutable,
+            edition_nonce: metadata.edition_nonce,
+            token_standard: Some(TokenStandard::NonFungible),
+            collection: metadata.collection.map(|collection| Collection {
+                verified: collection.verified,
+                key: collection.key,
+            }),
+            uses: None,
+            token_program_version: TokenProgramVersion::Original,
+            creators: metadata.creators.into_iter().map(Creator::from).collect(),
+        }
+    }
+}
+
+// Rebuilds the leaf bubblegum stores for a nonce, hashing the metadata the same way it does at mint
+pub fn leaf_schema(tree: &Pubkey, owner: Pubkey, delegate: Pubkey, nonce: u64, metadata: &MetadataArgs) -> Result<LeafSchema> {
+    Ok(LeafSchema::V1 {
+        id: get_asset_id(tree, nonce),
+        owner,
+        delegate,
+        nonce,
+        data_hash: hash_metadata(metadata)?,
+        creator_hash: hash_creators(&metadata.creators),
+    })
+}
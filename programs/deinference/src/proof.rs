@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::{accounts::TreeConfig, programs::SPL_ACCOUNT_COMPRESSION_ID};
+use crate::{error::Errors, merkle_tree::{proof_length, root_age, tree_authority}};
+
+// Maps the proof nodes passed in remaining_accounts into the (account, is_writable, is_signer)
+// tuples the bubblegum cpi builders expect
+pub fn proof_accounts<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>]
+) -> Vec<(&'a AccountInfo<'info>, bool, bool)> {
+    remaining_accounts
+        .iter()
+        .map(|node| (node, false, false))
+        .collect()
+}
+
+// Whether tree is a compression program tree whose header authority is tree_config, the bubblegum
+// config pda derived from it. Checked as an account constraint so a wrong tree fails before any
+// other account or cpi
+pub fn is_bubblegum_tree(tree: &AccountInfo, tree_config: &Pubkey) -> bool {
+    *tree.owner == SPL_ACCOUNT_COMPRESSION_ID
+        && TreeConfig::find_pda(tree.key).0 == *tree_config
+        && tree.try_borrow_data().is_ok_and(|data| tree_authority(&data) == Some(tree_config.to_bytes()))
+}
+
+// Errors unless exactly max_depth - canopy_depth proof nodes were passed for the tree
+pub fn require_proof_length(tree: &AccountInfo, remaining_accounts: &[AccountInfo]) -> Result<()> {
+    let expected = proof_length(&tree.try_borrow_data()?).ok_or(Errors::TreeAccountTooSmall)?;
+    require_eq!(remaining_accounts.len(), expected, Errors::InvalidProofLength);
+    Ok(())
+}
+
+// Errors unless root is one of the tree's last max_root_age roots, so clients can refetch the
+// proof instead of failing on a root that has since changed
+pub fn require_recent_root(tree: &AccountInfo, root: &[u8; 32], max_root_age: u64) -> Result<()> {
+    let age = root_age(&tree.try_borrow_data()?, root);
+    require!(age.is_some_and(|age| age <= max_root_age), Errors::RootTooStale);
+    Ok(())
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle_tree::MERKLE_TREE_HEADER_SIZE;
+
+    #[test]
+    fn test_is_bubblegum_tree() {
+        let key = Pubkey::new_unique();
+        let (tree_config, _) = TreeConfig::find_pda(&key);
+        let (mut lamports, mut data) = (0, vec![0; MERKLE_TREE_HEADER_SIZE]);
+        data[10..42].copy_from_slice(tree_config.as_ref());
+        let mut tree = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &SPL_ACCOUNT_COMPRESSION_ID, false, 0);
+
+        assert!(is_bubblegum_tree(&tree, &tree_config));
+        assert!(!is_bubblegum_tree(&tree, &Pubkey::new_unique()));
+
+        // A tree whose authority is not the config, such as one created directly with the compression program
+        tree.try_borrow_mut_data().unwrap()[10..42].copy_from_slice(&[1; 32]);
+        assert!(!is_bubblegum_tree(&tree, &tree_config));
+        tree.try_borrow_mut_data().unwrap()[10..42].copy_from_slice(tree_config.as_ref());
+
+        let system_program = anchor_lang::system_program::ID;
+        tree.owner = &system_program;
+        assert!(!is_bubblegum_tree(&tree, &tree_config));
+    }
+}
@@ -1,6 +1,23 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey::MAX_SEED_LEN;
 use strum_macros::Display;
-use crate::error::Errors;
+use crate::{error::Errors, metadata::KnowledgeCreator};
+
+pub const MAX_TREES: usize = 16;
+pub const MAX_MINTERS: usize = 16;
+pub const TREE_OWNER_SEED: &[u8] = b"tree_owner";
+
+// Seeds of the pda acting as this program's authority over a tree for a given role.
+// An empty role adds no bytes to the derivation, so it yields the original tree_owner pda
+pub fn tree_authority_seeds<'a>(role: &'a [u8], tree: &'a Pubkey) -> [&'a [u8]; 3] {
+    [TREE_OWNER_SEED, role, tree.as_ref()]
+}
+
+// A role is a single pda seed, so it is bound by the runtime's seed length. Checked on an account
+// declared before the tree authority so an oversized role fails before its seeds are derived
+pub fn is_valid_role(role: &[u8]) -> bool {
+    role.len() <= MAX_SEED_LEN
+}
 
 #[account]
 pub struct ProgramState {
@@ -32,6 +49,125 @@ impl ProgramState {
     }
 }
 
+// Account storing the configuration and stats of a tree created by this program
+#[account]
+pub struct TreeRegistry {
+    pub creator: Pubkey,       // payer that created the tree
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub canopy_depth: u32,
+    pub created_at: i64,       // Timestamp of creation
+    pub num_minted: u64,      // leaves currently in the tree
+    pub collection_minted: u64, // leaves minted into a collection, for reconciling collection sizes
+    pub last_nonce: u64,      // nonce of the most recently minted leaf
+    pub num_appended: u64,    // leaves ever appended, not decremented by burns since leaves are only appended
+}
+
+impl TreeRegistry {
+    // 2^max_depth, saturating for depths that do not fit in a u64
+    pub fn capacity(&self) -> u64 {
+        1u64.checked_shl(self.max_depth).unwrap_or(u64::MAX)
+    }
+
+    pub fn remaining_capacity(&self) -> u64 {
+        self.capacity().saturating_sub(self.num_appended)
+    }
+
+    // Errors if the tree cannot fit `count` more leaves
+    pub fn ensure_capacity(&self, count: u64) -> Result<()> {
+        let total = self.num_appended.checked_add(count).ok_or(Errors::CounterOverflow)?;
+        require!(total <= self.capacity(), Errors::TreeFull);
+        Ok(())
+    }
+
+    pub fn record_mints(&mut self, count: u64) -> Result<()> {
+        self.ensure_capacity(count)?;
+        self.num_appended = self.num_appended.checked_add(count).ok_or(Errors::CounterOverflow)?;
+        self.num_minted = self.num_minted.checked_add(count).ok_or(Errors::CounterOverflow)?;
+        Ok(())
+    }
+
+    // A leaf put back in place of a removed one, so it takes no new space in the tree
+    pub fn record_restore(&mut self) -> Result<()> {
+        self.num_minted = self.num_minted.checked_add(1).ok_or(Errors::CounterOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_removal(&mut self) -> Result<()> {
+        self.num_minted = self.num_minted.checked_sub(1).ok_or(Errors::CounterUnderflow)?;
+        Ok(())
+    }
+
+    pub fn record_collection_mint(&mut self) -> Result<()> {
+        self.collection_minted = self.collection_minted.checked_add(1).ok_or(Errors::CounterOverflow)?;
+        Ok(())
+    }
+}
+
+// Allowlist of signers that may mint into a tree, managed by the tree creator
+#[account]
+pub struct MintAuthority {
+    pub minters: Vec<Pubkey>,
+}
+
+impl MintAuthority {
+    pub fn is_minter(&self, key: &Pubkey) -> bool {
+        self.minters.contains(key)
+    }
+
+    // Adding an existing minter is a no-op
+    pub fn add_minter(&mut self, minter: Pubkey) -> Result<()> {
+        if self.is_minter(&minter) {
+            return Ok(());
+        }
+        require!(self.minters.len() < MAX_MINTERS, Errors::TooManyMinters);
+        self.minters.push(minter);
+        Ok(())
+    }
+
+    pub fn remove_minter(&mut self, minter: &Pubkey) -> Result<()> {
+        let position = self.minters.iter().position(|key| key == minter)
+            .ok_or(Errors::MinterNotFound)?;
+        self.minters.remove(position);
+        Ok(())
+    }
+}
+
+// Marks a leaf as frozen while it exists, seeded by the tree and leaf nonce
+#[account]
+pub struct FrozenMarker {}
+
+// Tree delegate proposed by the tree creator, applied once the proposed key accepts
+#[account]
+pub struct PendingOwner {
+    pub creator: Pubkey,      // proposer, refunded when the record is closed
+    pub proposed: Pubkey,
+}
+
+// Maps a content hash to its uri, seeded by the registering authority and the hash so each
+// authority registers a hash at most once and cannot claim it for anyone else
+#[account]
+pub struct ContentRegistry {
+    pub hash: [u8; 32],
+    pub authority: Pubkey,
+    pub uri: String,
+}
+
+// Program wide switches, managed by the admin
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub paused: bool, // halts mutating instructions while set
+}
+
+// Reusable royalties bundle that mint_knowledge can read instead of inline creators
+#[account]
+pub struct CreatorsConfig {
+    pub authority: Pubkey,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<KnowledgeCreator>,
+}
+
 // Account storing data about an inference task collection NFT
 #[account]
 pub struct TaskData {
@@ -85,6 +221,34 @@ pub struct ResultEntry {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_tree_authority_seeds() {
+        let tree = Pubkey::new_unique();
+        let (default_owner, _) = Pubkey::find_program_address(&[TREE_OWNER_SEED, tree.as_ref()], &crate::ID);
+
+        // The default role derives the original tree_owner pda
+        let (empty_role, _) = Pubkey::find_program_address(&tree_authority_seeds(&[], &tree), &crate::ID);
+        assert_eq!(empty_role, default_owner);
+
+        // Distinct roles derive distinct authorities that can each sign
+        let (minter, minter_bump) = Pubkey::find_program_address(&tree_authority_seeds(b"minter", &tree), &crate::ID);
+        let (burner, burner_bump) = Pubkey::find_program_address(&tree_authority_seeds(b"burner", &tree), &crate::ID);
+        assert_ne!(minter, burner);
+        assert_ne!(minter, default_owner);
+
+        let [seed, role, tree_seed] = tree_authority_seeds(b"minter", &tree);
+        assert_eq!(Pubkey::create_program_address(&[seed, role, tree_seed, &[minter_bump]], &crate::ID).unwrap(), minter);
+        let [seed, role, tree_seed] = tree_authority_seeds(b"burner", &tree);
+        assert_eq!(Pubkey::create_program_address(&[seed, role, tree_seed, &[burner_bump]], &crate::ID).unwrap(), burner);
+    }
+
+    #[test]
+    fn test_is_valid_role() {
+        assert!(is_valid_role(&[]));
+        assert!(is_valid_role(&[0u8; MAX_SEED_LEN]));
+        assert!(!is_valid_role(&[0u8; MAX_SEED_LEN + 1]));
+    }
+
     #[test]
     fn test_get_tree() {
         let tree_1 = TreeInfo {
@@ -150,4 +314,130 @@ mod tests {
         let result = program_state.increment_index(non_existent_tree);
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_ensure_capacity() {
+        let mut tree_registry = TreeRegistry {
+            creator: Pubkey::new_unique(),
+            max_depth: 3,
+            max_buffer_size: 8,
+            canopy_depth: 0,
+            created_at: 0,
+            num_minted: 7,
+            collection_minted: 0,
+            last_nonce: 0,
+            num_appended: 7,
+        };
+
+        // One leaf left
+        assert!(tree_registry.ensure_capacity(1).is_ok());
+        assert_eq!(tree_registry.ensure_capacity(2).unwrap_err(), Errors::TreeFull.into());
+
+        tree_registry.num_appended = 8;
+        assert_eq!(tree_registry.ensure_capacity(1).unwrap_err(), Errors::TreeFull.into());
+
+        // Burns free no space, the burned leaves stay appended
+        tree_registry.num_minted = 0;
+        assert_eq!(tree_registry.ensure_capacity(1).unwrap_err(), Errors::TreeFull.into());
+
+        // Counts that would wrap are rejected rather than passing the capacity check
+        assert_eq!(tree_registry.ensure_capacity(u64::MAX).unwrap_err(), Errors::CounterOverflow.into());
+
+        tree_registry.max_depth = 64;
+        assert_eq!(tree_registry.capacity(), u64::MAX);
+    }
+
+    #[test]
+    fn test_record_mints_at_capacity() {
+        let mut tree_registry = TreeRegistry {
+            creator: Pubkey::new_unique(),
+            max_depth: 3,
+            max_buffer_size: 8,
+            canopy_depth: 0,
+            created_at: 0,
+            num_minted: 6,
+            collection_minted: 0,
+            last_nonce: 0,
+            num_appended: 6,
+        };
+
+        tree_registry.record_mints(2).unwrap();
+        assert_eq!(tree_registry.num_minted, 8);
+        assert_eq!(tree_registry.record_mints(1).unwrap_err(), Errors::TreeFull.into());
+        assert_eq!(tree_registry.num_minted, 8, "Rejected mints are not counted");
+        assert_eq!(tree_registry.num_appended, 8);
+    }
+
+    #[test]
+    fn test_record_removal_below_zero() {
+        let mut tree_registry = TreeRegistry {
+            creator: Pubkey::new_unique(),
+            max_depth: 3,
+            max_buffer_size: 8,
+            canopy_depth: 0,
+            created_at: 0,
+            num_minted: 1,
+            collection_minted: 0,
+            last_nonce: 0,
+            num_appended: 1,
+        };
+
+        tree_registry.record_removal().unwrap();
+        assert_eq!(tree_registry.num_minted, 0);
+        assert_eq!(tree_registry.record_removal().unwrap_err(), Errors::CounterUnderflow.into());
+        assert_eq!(tree_registry.num_minted, 0);
+        assert_eq!(tree_registry.num_appended, 1, "Removals leave the append count alone");
+
+        tree_registry.record_restore().unwrap();
+        assert_eq!(tree_registry.num_minted, 1);
+        assert_eq!(tree_registry.num_appended, 1);
+    }
+
+    #[test]
+    fn test_mint_authority() {
+        let creator = Pubkey::new_unique();
+        let minter = Pubkey::new_unique();
+        let mut mint_authority = MintAuthority { minters: vec![creator] };
+
+        assert!(!mint_authority.is_minter(&minter));
+        mint_authority.add_minter(minter).unwrap();
+        mint_authority.add_minter(minter).unwrap();
+        assert!(mint_authority.is_minter(&minter));
+        assert_eq!(mint_authority.minters.len(), 2);
+
+        mint_authority.remove_minter(&minter).unwrap();
+        assert!(!mint_authority.is_minter(&minter));
+        assert_eq!(mint_authority.remove_minter(&minter).unwrap_err(), Errors::MinterNotFound.into());
+
+        // The allowlist is capped
+        for _ in 1..MAX_MINTERS {
+            mint_authority.add_minter(Pubkey::new_unique()).unwrap();
+        }
+        assert_eq!(mint_authority.add_minter(minter).unwrap_err(), Errors::TooManyMinters.into());
+    }
+
+    #[test]
+    fn test_remaining_capacity() {
+        let mut tree_registry = TreeRegistry {
+            creator: Pubkey::new_unique(),
+            max_depth: 3,
+            max_buffer_size: 8,
+            canopy_depth: 0,
+            created_at: 0,
+            num_minted: 0,
+            collection_minted: 0,
+            last_nonce: 0,
+            num_appended: 0,
+        };
+        assert_eq!(tree_registry.remaining_capacity(), 8);
+
+        tree_registry.record_mints(7).unwrap();
+        assert_eq!(tree_registry.remaining_capacity(), 1);
+
+        tree_registry.record_removal().unwrap();
+        assert_eq!(tree_registry.remaining_capacity(), 1, "Burned leaves still take space");
+
+        tree_registry.record_mints(1).unwrap();
+        assert_eq!(tree_registry.remaining_capacity(), 0);
+    }
+}
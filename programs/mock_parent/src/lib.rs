@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use knowledge_manager::cpi::accounts::CreateTreeCpi;
+use knowledge_manager::program::KnowledgeManager;
+use knowledge_manager::{MplBubblegum, Noop, SplAccountCompression};
+
+declare_id!("BuAatdRptptbWU3y7TvYUngPWKCPTfqKpmQfuPzRbxKT");
+
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+
+// Stands in for a governance program that owns knowledge trees through its own pda
+#[program]
+pub mod mock_parent {
+    use super::*;
+
+    pub fn create_governed_tree<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateGovernedTree<'info>>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        let bump = ctx.bumps.governance;
+        let signer_seeds: &[&[u8]] = &[GOVERNANCE_SEED, &[bump]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.knowledge_manager.to_account_info(),
+            CreateTreeCpi {
+                tree: ctx.accounts.tree.to_account_info(),
+                tree_config: ctx.accounts.tree_config.to_account_info(),
+                payer: ctx.accounts.payer.to_account_info(),
+                authority: ctx.accounts.governance.to_account_info(),
+                parent_program: ctx.accounts.parent_program.to_account_info(),
+                tree_owner: ctx.accounts.tree_owner.to_account_info(),
+                program_state: ctx.accounts.program_state.to_account_info(),
+                tree_registry: ctx.accounts.tree_registry.to_account_info(),
+                mint_authority: ctx.accounts.mint_authority.to_account_info(),
+                config: ctx.accounts.config.to_account_info(),
+                mpl_bubblegum_program: ctx.accounts.mpl_bubblegum_program.to_account_info(),
+                log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+                compression_program: ctx.accounts.compression_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            std::slice::from_ref(&signer_seeds),
+        );
+
+        let authority_seeds = vec![GOVERNANCE_SEED.to_vec(), vec![bump]];
+        knowledge_manager::cpi::create_tree_cpi(cpi_ctx, max_depth, max_buffer_size, 0, authority_seeds)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateGovernedTree<'info> {
+    /// CHECK: Checked by knowledge_manager
+    #[account(mut)]
+    pub tree: UncheckedAccount<'info>,
+
+    /// CHECK: Checked by knowledge_manager
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [GOVERNANCE_SEED], bump)]
+    /// CHECK: Signing pda only
+    pub governance: UncheckedAccount<'info>,
+
+    /// CHECK: This program, passed so knowledge_manager can derive the governance pda
+    #[account(address = crate::ID)]
+    pub parent_program: UncheckedAccount<'info>,
+
+    /// CHECK: Checked by knowledge_manager
+    pub tree_owner: UncheckedAccount<'info>,
+
+    /// CHECK: Checked by knowledge_manager
+    #[account(mut)]
+    pub program_state: UncheckedAccount<'info>,
+
+    /// CHECK: Checked by knowledge_manager
+    #[account(mut)]
+    pub tree_registry: UncheckedAccount<'info>,
+
+    /// CHECK: Checked by knowledge_manager
+    #[account(mut)]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Checked by knowledge_manager
+    pub config: UncheckedAccount<'info>,
+
+    pub mpl_bubblegum_program: Program<'info, MplBubblegum>,
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+
+    pub knowledge_manager: Program<'info, KnowledgeManager>,
+    pub system_program: Program<'info, System>,
+}
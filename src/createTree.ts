@@ -8,6 +8,7 @@ import { findTreeConfigPda, MPL_BUBBLEGUM_PROGRAM_ID } from "@metaplex-foundatio
 export const createTree = async (maxDepthSizePair : ValidDepthSizePair) => {
 
   const wallet = provider.wallet as Wallet;
+  const canopyDepth = maxDepthSizePair.maxDepth - 5;
 
   // Create tree account and allocate necessary space
   const tree = Keypair.generate();
@@ -16,7 +17,7 @@ export const createTree = async (maxDepthSizePair : ValidDepthSizePair) => {
     tree.publicKey,
     wallet.publicKey,
     maxDepthSizePair,
-    maxDepthSizePair.maxDepth - 5
+    canopyDepth
   );
 
   const allocTx = new Transaction().add(allocTreeIx);
@@ -36,7 +37,8 @@ export const createTree = async (maxDepthSizePair : ValidDepthSizePair) => {
   );
 
   const tx = await program.methods
-    .createTree(maxDepthSizePair.maxDepth, maxDepthSizePair.maxBufferSize)
+    // The empty role creates the tree under the default tree_owner pda
+    .createTree(maxDepthSizePair.maxDepth, maxDepthSizePair.maxBufferSize, canopyDepth, Buffer.from([]))
     // Do not pass accounts that are automatically resolved
     .accounts({
       tree: tree.publicKey,
@@ -38,6 +38,22 @@ const main = async () => {
     let programStateData = await program.account.programState.fetch(programStatePda);
     console.log("Program state initialized:", programStateData)
 
+    // Tree creation reads the pause switch from the config pda
+    let [configPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("config")],
+        program.programId
+    );
+    const configAccountInfo = await provider.connection.getAccountInfo(configPda);
+    if (!configAccountInfo) {
+        console.log("Program config not yet initialized, intializing...");
+        const tx = await program.methods.initConfig()
+        .accounts({
+        creator: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc({commitment: 'confirmed'});
+    }
+
     // Create a merkle tree
     const maxDepthSizePair: ValidDepthSizePair = {
         maxDepth: 14,
@@ -43,16 +43,17 @@ export const mintCNFT = async (leafOwner: Keypair, tree: PublicKey, metadata: Mo
     
     // Mint model to collection (leafOwner should not pay, check who is paying)
     const tx = await program.methods
-      .mintToCollection(metadata.name, metadata.symbol, metadata.hash, 0)
+      .mintToCollection(metadata.name, metadata.symbol, metadata.hash, 0, Buffer.from([]))
       .accounts({
         payer: wallet.payer.publicKey,
-        treeAuth: treeConfig,
+        treeConfig: treeConfig,
         leafOwner: leafOwner.publicKey,
         tree: tree,
+        collectionAuthority: wallet.payer.publicKey,
         collectionMint: collectionNFT.mintAddress,
         collectionMetadata: collectionNFT.metadataAddress,
+        collectionEdition: collectionNFT.masterEditionAddress,
         bubblegumSigner: bubblegumSigner,
-        editionAccount: collectionNFT.masterEditionAddress,
       })
     .rpc({ commitment: 'confirmed' });
 
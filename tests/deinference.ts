@@ -1,20 +1,28 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { KnowledgeManager } from "../target/types/knowledge_manager";
+import { MockParent } from "../target/types/mock_parent";
 import {
   findLeafAssetIdPda,
+  fetchTreeConfigFromSeeds,
+  findVoucherPda,
+  getAssetWithProof,
+  hashMetadataCreators,
+  hashMetadataData,
+  mintV1,
   MPL_BUBBLEGUM_PROGRAM_ID,
 } from '@metaplex-foundation/mpl-bubblegum';
 import { fetchDigitalAsset, mplTokenMetadata, updateV1, } from "@metaplex-foundation/mpl-token-metadata";
 import { MPL_TOKEN_METADATA_PROGRAM_ID, createNft } from '@metaplex-foundation/mpl-token-metadata';
-import { percentAmount, PublicKey as UmiPK, generateSigner, signerIdentity, createSignerFromKeypair, KeypairSigner, request } from '@metaplex-foundation/umi';
+import { percentAmount, PublicKey as UmiPK, generateSigner, signerIdentity, createSignerFromKeypair, KeypairSigner, request, unwrapOption } from '@metaplex-foundation/umi';
 import { createUmi } from '@metaplex-foundation/umi-bundle-defaults';
 import { dasApi } from '@metaplex-foundation/digital-asset-standard-api';
 import { PublicKey, Keypair, TransactionConfirmationStrategy, Transaction, sendAndConfirmTransaction, TransactionSignature, SYSVAR_INSTRUCTIONS_PUBKEY,
   Ed25519Program, 
-  LAMPORTS_PER_SOL} from "@solana/web3.js";
+  LAMPORTS_PER_SOL,
+  ComputeBudgetProgram} from "@solana/web3.js";
 import { assert } from "chai";
-import { ChangeLogEventV1, ConcurrentMerkleTreeAccount, createAllocTreeIx, deserializeChangeLogEventV1, ValidDepthSizePair } from "@solana/spl-account-compression";
+import { ChangeLogEventV1, ConcurrentMerkleTreeAccount, createAllocTreeIx, deserializeChangeLogEventV1, MerkleTree, ValidDepthSizePair } from "@solana/spl-account-compression";
 import {  } from "@coral-xyz/anchor"
 import { bs58 } from "@coral-xyz/anchor/dist/cjs/utils/bytes";
 import { base58 } from "@metaplex-foundation/umi/serializers";
@@ -38,6 +46,103 @@ describe("deinference", () => {
 
     return await connection.confirmTransaction(confirmationStrategy, 'confirmed');
   }
+
+  // Extract the leaf index from the compression change log emitted by a mint
+  const getLeafIndex = async (signature: TransactionSignature) => {
+    const transactionDetails = await connection.getTransaction(signature, {
+      maxSupportedTransactionVersion: 1,
+      commitment: 'confirmed'
+    });
+
+    let changeLogEvents: ChangeLogEventV1[] = [];
+    transactionDetails.meta?.innerInstructions?.forEach((compiledIx) => {
+      compiledIx.instructions.forEach((innerIx) => {
+        try {
+          changeLogEvents.push(
+            deserializeChangeLogEventV1(Buffer.from(bs58.decode(innerIx.data)))
+          );
+        } catch (__) {
+          // this noop cpi is not a changelog event. do nothing with it.
+        }
+      });
+    });
+    return changeLogEvents[0].index;
+  }
+
+  // Mint a knowledge leaf to the shared tree and return its asset id
+  const mintLeaf = async (leafOwner: PublicKey) => {
+    const tx = await program.methods
+      .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], Buffer.from([]), null, false)
+      .accounts({
+        payer: wallet.publicKey,
+        tree: tree.publicKey,
+        treeConfig: tree_config,
+        leafOwner: leafOwner
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed'});
+    await confirmTransaction(tx);
+
+    const leafIndex = await getLeafIndex(tx);
+    const [assetId] = findLeafAssetIdPda(umi, {
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      leafIndex: leafIndex,
+    });
+    return assetId;
+  }
+
+  // Allocate and create a new tree, returning its keypair and config pda
+  const createNewTree = async (depthSizePair: ValidDepthSizePair, canopy: number, role: Buffer = Buffer.from([])) => {
+    const newTree = Keypair.generate();
+    const allocTreeIx = await createAllocTreeIx(
+      connection,
+      newTree.publicKey,
+      wallet.publicKey,
+      depthSizePair,
+      canopy
+    );
+    await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(allocTreeIx),
+      [wallet.payer, newTree],
+      { commitment: 'confirmed' }
+    );
+
+    const [newTreeConfig] = PublicKey.findProgramAddressSync(
+      [newTree.publicKey.toBuffer()],
+      new PublicKey(MPL_BUBBLEGUM_PROGRAM_ID)
+    );
+
+    const tx = await program.methods
+      .createTree(depthSizePair.maxDepth, depthSizePair.maxBufferSize, canopy, role)
+      .accounts({
+        tree: newTree.publicKey,
+        treeConfig: newTreeConfig,
+        payer: wallet.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    return { tree: newTree, treeConfig: newTreeConfig };
+  }
+
+  // Number of tree updates a fetched root may lag behind when transferring in these tests
+  const maxRootAge = new anchor.BN(8);
+
+  // Fetch the current leaf arguments and the proof not covered by the canopy
+  const getLeafProof = async (assetId: UmiPK) => {
+    const asset = await getAssetWithProof(umi, assetId, { truncateCanopy: true });
+    return {
+      root: Array.from(asset.root),
+      dataHash: Array.from(asset.dataHash),
+      creatorHash: Array.from(asset.creatorHash),
+      nonce: new anchor.BN(asset.nonce.toString()),
+      index: asset.index,
+      proof: asset.proof.map((node) => ({
+        pubkey: new PublicKey(node),
+        isSigner: false,
+        isWritable: false,
+      })),
+    };
+  }
   
   let programStateAccountInfo;
   let programStateData;
@@ -54,6 +159,7 @@ describe("deinference", () => {
   const wallet = provider.wallet as anchor.Wallet;
 
   const program = anchor.workspace.KnowledgeManager as Program<KnowledgeManager>;
+  const mockParent = anchor.workspace.MockParent as Program<MockParent>;
   const connection = provider.connection;
 
   // Setup umi
@@ -71,6 +177,12 @@ describe("deinference", () => {
     program.programId
   );
 
+  // Program wide config holding the pause switch
+  let [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
   // Derive the PDA (task_data)
   collection_mint = generateSigner(umi);
   let [taskDataPda] = PublicKey.findProgramAddressSync(
@@ -103,6 +215,12 @@ describe("deinference", () => {
     program.programId
   );
 
+  // Derive tree registry pda
+  const [treeRegistryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("registry"), tree.publicKey.toBuffer()],
+    program.programId
+  );
+
   // Derive tree config account pda (owned by bubblegum program)
   let [tree_config] = PublicKey.findProgramAddressSync(
       [tree.publicKey.toBuffer()], // Same seed as in the Rust program
@@ -147,6 +265,15 @@ describe("deinference", () => {
       }).signers([wallet.payer]).rpc({ commitment: 'confirmed'});
     }
 
+    const configAccountInfo = await provider.connection.getAccountInfo(configPda);
+    if (configAccountInfo) {
+      const closeConfigAccountTx = await program.methods.closeAccount()
+      .accounts({
+        pdaAccount: configPda,
+        receiver: wallet.payer.publicKey
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed'});
+    }
+
     const taskDataStateAccountInfo = await provider.connection.getAccountInfo(taskDataPda);
     if (taskDataStateAccountInfo) {
       const closeTaskDataAccountTx = await program.methods.closeAccount()
@@ -163,186 +290,2795 @@ describe("deinference", () => {
       program.programId
     );
 
-    const requestStateAccountInfo = await provider.connection.getAccountInfo(requestStatePda);
-    if (requestStateAccountInfo) {
-      console.log("Attempting to close request state");
-      const closeRequestStateAccountTx = await program.methods.closeAccount()
+    const requestStateAccountInfo = await provider.connection.getAccountInfo(requestStatePda);
+    if (requestStateAccountInfo) {
+      console.log("Attempting to close request state");
+      const closeRequestStateAccountTx = await program.methods.closeAccount()
+      .accounts({
+        pdaAccount: requestStatePda,
+        receiver: wallet.payer.publicKey
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed'});
+      console.log("Request state account closed:", closeRequestStateAccountTx);
+    } 
+    
+    // Give tree account space
+    const allocTreeIx = await createAllocTreeIx(
+      connection,
+      tree.publicKey,
+      wallet.publicKey,
+      maxDepthSizePair,
+      canopyDepth
+    );
+
+    const tx = new Transaction().add(allocTreeIx);
+    const txSignature = await sendAndConfirmTransaction(
+      connection,
+      tx,
+      [wallet.payer, tree],
+      {
+        commitment: 'confirmed',
+      }
+    );
+
+    console.log('Tree Address:', tree.publicKey.toBase58());
+
+    // Create collection mint
+    const createNftTx = await createNft(umi, {
+      mint: collection_mint,
+      sellerFeeBasisPoints: percentAmount(0),
+      name: 'TEST-COLLECTION',
+      uri: "https://raw.githubusercontent.com/robertLam04/DEInference/main/example_task.json",
+      isCollection: true
+    }).sendAndConfirm(umi);
+    
+    const createNftSignature = base58.deserialize(createNftTx.signature)[0];
+    console.log(`https://explorer.solana.com/tx/${createNftSignature}?cluster=devnet`)
+
+    await confirmTransaction(createNftSignature);
+
+    const updateNftUpdateAuthTx = await updateV1(umi, {
+      mint: collection_mint.publicKey,
+      newUpdateAuthority: tree_owner.toBase58() as UmiPK
+    }).sendAndConfirm(umi);
+
+    const updateNftUpdateAuthSignature = base58.deserialize(updateNftUpdateAuthTx.signature)[0];
+    console.log(`https://explorer.solana.com/tx/${updateNftUpdateAuthSignature}?cluster=devnet`)
+
+    // Derive collection metadata pda account
+    collectionMetadataAccount = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata", "utf8"),
+        new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID).toBuffer(),
+        new PublicKey(collection_mint.publicKey).toBuffer(),
+      ],
+      new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID)
+    )[0];
+  });
+
+  it("Initializes the program state pda", async () => {
+    
+    // Call the initialize function
+    const tx = await program.methods.initialize()
+    .accounts({
+      payer: wallet.publicKey,
+    })
+    .signers([wallet.payer])
+    .rpc({commitment: 'confirmed'});
+
+    // Fetch the tree_state account
+    programStateAccountInfo = await provider.connection.getAccountInfo(programStatePda);
+    programStateData = await program.account.programState.fetch(programStatePda);
+
+    assert.strictEqual(
+      programStateAccountInfo.owner.toString(),
+      program.programId.toString(),
+      "tree_state account is not owned by the program"
+    );
+
+    assert.strictEqual(
+      programStateData.creator.toString(),
+      "2gUrmvYsLTpXB5VwjP2ZpXD4kY4HWRP89aDzQQ7TKbwh",
+      "unexpected creator");
+
+    assert.strictEqual(
+      programStateAccountInfo.data.length,
+      46 + 16 * 66, // space = account disc (8) + pubkey (32) + vec size (4) + tree count (2) + max_#_trees (16) * tree info (66)
+      "tree_state account data size is incorrect"
+    );
+  });
+
+  it("Initializes the program config pda", async () => {
+    const tx = await program.methods.initConfig()
+    .accounts({
+      creator: wallet.publicKey,
+    })
+    .signers([wallet.payer])
+    .rpc({commitment: 'confirmed'});
+    await confirmTransaction(tx);
+
+    const config = await program.account.config.fetch(configPda);
+    assert.ok(config.admin.equals(wallet.publicKey), "Admin should be the program creator");
+    assert.isFalse(config.paused, "Program should start unpaused");
+  });
+
+  it("Creates an empty merkle tree", async () => {
+    let treeCreatedEvent;
+    const listener = program.addEventListener("treeCreated", (event, slot) => {
+      treeCreatedEvent = event;
+    });
+
+    const tx = await program.methods
+    .createTree(maxDepthSizePair.maxDepth, maxDepthSizePair.maxBufferSize, canopyDepth, Buffer.from([]))
+    // Do not pass accounts that are automatically resolved
+    .accounts({
+      tree: tree.publicKey,
+      treeConfig: tree_config,
+      payer: wallet.publicKey,
+    }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed'});
+    await confirmTransaction(tx);
+    await program.removeEventListener(listener);
+
+    assert.ok(treeCreatedEvent, "TreeCreated event should be emitted");
+    assert.ok(treeCreatedEvent.tree.equals(tree.publicKey));
+    assert.ok(treeCreatedEvent.creator.equals(wallet.publicKey));
+    assert.strictEqual(treeCreatedEvent.maxDepth, maxDepthSizePair.maxDepth);
+    assert.strictEqual(treeCreatedEvent.maxBufferSize, maxDepthSizePair.maxBufferSize);
+
+    treeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(
+      connection,
+      tree.publicKey
+    );
+
+    console.log('MaxBufferSize', treeAccount.getMaxBufferSize());
+    console.log('MaxDepth', treeAccount.getMaxDepth());
+    console.log('Tree Authority', treeAccount.getAuthority().toString());
+    console.log('Initial Root:', treeAccount.getCurrentRoot().toString('hex'));
+
+    assert.ok(programStateAccountInfo, "Program state account should be initialized before creating tree"); 
+    assert.strictEqual(treeAccount.getMaxDepth(), maxDepthSizePair.maxDepth, "Unexpected max depth");
+    assert.strictEqual(treeAccount.getMaxBufferSize(), maxDepthSizePair.maxBufferSize, "Unexepected max buffer size");
+    
+    programStateData = await program.account.programState.fetch(programStatePda);
+    assert.strictEqual(
+      programStateData.treeCount,
+      1,
+      "Unexpected number of merkle trees"
+    );
+
+    const treeRegistry = await program.account.treeRegistry.fetch(treeRegistryPda);
+    assert.ok(treeRegistry.creator.equals(wallet.publicKey), "Unexpected tree creator");
+    assert.strictEqual(treeRegistry.maxDepth, maxDepthSizePair.maxDepth);
+    assert.strictEqual(treeRegistry.maxBufferSize, maxDepthSizePair.maxBufferSize);
+    assert.strictEqual(treeRegistry.canopyDepth, canopyDepth);
+    assert.strictEqual(treeRegistry.numMinted.toNumber(), 0);
+  });
+
+  it("Mints an NFT to an existing merkle tree", async () => {
+    const leafOwner = Keypair.generate();
+
+    assert.ok(treeAccount, "Tree account should exist before minting NFTs");
+    const leafCountBefore = treeAccount.tree.rightMostPath.index;
+    const numMintedBefore = (await program.account.treeRegistry.fetch(treeRegistryPda)).numMinted.toNumber();
+    
+    const tx: TransactionSignature = await program.methods
+    .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], Buffer.from([]), null, false)
+    .accounts({
+      payer: wallet.publicKey,
+      tree: tree.publicKey,
+      treeConfig: tree_config,
+      leafOwner: leafOwner.publicKey
+    }).signers([wallet.payer]).rpc({ commitment: 'confirmed'});
+
+    treeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(
+      connection,
+      tree.publicKey
+    );
+    assert.strictEqual(
+      treeAccount.tree.rightMostPath.index,
+      leafCountBefore + 1,
+      "Active leaf count should increment after mint"
+    );
+
+    const treeRegistry = await program.account.treeRegistry.fetch(treeRegistryPda);
+    assert.strictEqual(treeRegistry.numMinted.toNumber(), numMintedBefore + 1);
+
+    const transactionDetails = await connection.getTransaction(tx, {
+      maxSupportedTransactionVersion: 1,
+      commitment: 'confirmed'
+    });
+
+    //Extract leaf index from change logs
+    let changeLogEvents: ChangeLogEventV1[] = [];
+    transactionDetails.meta?.innerInstructions?.forEach((compiledIx) => {
+      compiledIx.instructions.forEach((innerIx) => {
+        try {
+          // try to deserialize the cpi data as a changelog event
+          changeLogEvents.push(
+            deserializeChangeLogEventV1(Buffer.from(bs58.decode(innerIx.data)))
+          );
+        } catch (__) {
+          // this noop cpi is not a changelog event. do nothing with it.
+        }
+      });
+    });
+    const leafIndex = changeLogEvents[0].index;
+
+    const [assetId, bump] = await findLeafAssetIdPda(umi, {
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      leafIndex: leafIndex,
+    });
+
+    console.log("Leaf Index:", leafIndex);
+    console.log("Tree pk:", tree.publicKey.toBase58());
+
+    await confirmTransaction(tx);
+    const rpcAsset = await umi.rpc.getAsset(assetId);
+
+    console.log(`Transaction: https://explorer.solana.com/tx/${tx}?cluster=devnet`);
+    console.log("NFT Name:", rpcAsset.content.metadata.name);
+
+  });
+
+  it("Emits the minted leaf index and hash matching the tree state", async () => {
+    let leafMintedEvent;
+    const listener = program.addEventListener("leafMinted", (event) => { leafMintedEvent = event; });
+
+    await mintLeaf(Keypair.generate().publicKey);
+    await program.removeEventListener(listener);
+
+    treeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, tree.publicKey);
+    const rightMostPath = treeAccount.tree.rightMostPath;
+    assert.ok(leafMintedEvent, "LeafMinted should be emitted");
+    assert.strictEqual(leafMintedEvent.index, rightMostPath.index - 1);
+    assert.deepEqual(Buffer.from(leafMintedEvent.leafHash), rightMostPath.leaf.toBuffer());
+
+    const registry = await program.account.treeRegistry.fetch(treeRegistryPda);
+    assert.strictEqual(registry.lastNonce.toString(), leafMintedEvent.nonce.toString());
+  });
+
+  it("Emits leaf lifecycle events on mint, transfer and burn", async () => {
+    const leafOwner = Keypair.generate();
+    const newOwner = Keypair.generate();
+    let leafMintedEvent, leafTransferredEvent, leafBurnedEvent;
+
+    const listeners = [
+      program.addEventListener("leafMinted", (event) => { leafMintedEvent = event; }),
+      program.addEventListener("leafTransferred", (event) => { leafTransferredEvent = event; }),
+      program.addEventListener("leafBurned", (event) => { leafBurnedEvent = event; }),
+    ];
+
+    const assetId = await mintLeaf(leafOwner.publicKey);
+
+    let leaf = await getLeafProof(assetId);
+    const transferTx = await program.methods
+      .transferKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, maxRootAge)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+        newLeafOwner: newOwner.publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(transferTx);
+
+    leaf = await getLeafProof(assetId);
+    const burnTx = await program.methods
+      .burnKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: newOwner.publicKey,
+        leafDelegate: newOwner.publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([newOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(burnTx);
+
+    for (const listener of listeners) {
+      await program.removeEventListener(listener);
+    }
+
+    for (const event of [leafMintedEvent, leafTransferredEvent, leafBurnedEvent]) {
+      assert.ok(event, "Leaf event should be emitted");
+      assert.ok(event.tree.equals(tree.publicKey));
+      assert.strictEqual(event.index, leaf.index);
+      assert.strictEqual(event.nonce.toString(), leaf.nonce.toString());
+    }
+  });
+
+  it("Transfers a knowledge NFT to a new owner who can transfer it again", async () => {
+    const firstOwner = Keypair.generate();
+    const secondOwner = Keypair.generate();
+    const thirdOwner = Keypair.generate();
+
+    const assetId = await mintLeaf(firstOwner.publicKey);
+
+    let leaf = await getLeafProof(assetId);
+    const firstTx = await program.methods
+      .transferKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, maxRootAge)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: firstOwner.publicKey,
+        leafDelegate: firstOwner.publicKey,
+        newLeafOwner: secondOwner.publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([firstOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(firstTx);
+
+    let rpcAsset = await umi.rpc.getAsset(assetId);
+    assert.strictEqual(rpcAsset.ownership.owner.toString(), secondOwner.publicKey.toBase58());
+
+    // The new owner must be able to move the leaf on
+    leaf = await getLeafProof(assetId);
+    const secondTx = await program.methods
+      .transferKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, maxRootAge)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: secondOwner.publicKey,
+        leafDelegate: secondOwner.publicKey,
+        newLeafOwner: thirdOwner.publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([secondOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(secondTx);
+
+    rpcAsset = await umi.rpc.getAsset(assetId);
+    assert.strictEqual(rpcAsset.ownership.owner.toString(), thirdOwner.publicKey.toBase58());
+  });
+
+  it("Mints a leaf paid by one wallet and owned by another who can transfer it", async () => {
+    const leafOwner = Keypair.generate();
+    const leafDelegate = Keypair.generate();
+    const newOwner = Keypair.generate();
+
+    // The wallet only pays, ownership and delegation go to other keys
+    const mintTx = await program.methods
+      .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], Buffer.from([]), null, false)
+      .accounts({
+        payer: wallet.publicKey,
+        tree: tree.publicKey,
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafDelegate.publicKey,
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(mintTx);
+
+    const [assetId] = findLeafAssetIdPda(umi, {
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      leafIndex: await getLeafIndex(mintTx),
+    });
+    let rpcAsset = await umi.rpc.getAsset(assetId);
+    assert.strictEqual(rpcAsset.ownership.owner.toString(), leafOwner.publicKey.toBase58());
+    assert.strictEqual(rpcAsset.ownership.delegate?.toString(), leafDelegate.publicKey.toBase58());
+
+    const leaf = await getLeafProof(assetId);
+    const transferTx = await program.methods
+      .transferKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, maxRootAge)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafDelegate.publicKey,
+        newLeafOwner: newOwner.publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(transferTx);
+
+    rpcAsset = await umi.rpc.getAsset(assetId);
+    assert.strictEqual(rpcAsset.ownership.owner.toString(), newOwner.publicKey.toBase58());
+  });
+
+  it("Rejects a transfer with a root older than max_root_age_slots with RootTooStale", async () => {
+    const leafOwner = Keypair.generate();
+    const newOwner = Keypair.generate();
+    const assetId = await mintLeaf(leafOwner.publicKey);
+
+    const transferWith = (leaf: Awaited<ReturnType<typeof getLeafProof>>, maxAge: anchor.BN) => program.methods
+      .transferKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, maxAge)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+        newLeafOwner: newOwner.publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+
+    // Another mint advances the tree past the fetched root
+    const staleLeaf = await getLeafProof(assetId);
+    await mintLeaf(Keypair.generate().publicKey);
+
+    try {
+      await transferWith(staleLeaf, new anchor.BN(0));
+      assert.fail("Transfer with a stale root should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "RootTooStale");
+    }
+
+    // A refetched proof is current again
+    const freshLeaf = await getLeafProof(assetId);
+    await confirmTransaction(await transferWith(freshLeaf, new anchor.BN(0)));
+
+    const rpcAsset = await umi.rpc.getAsset(assetId);
+    assert.strictEqual(rpcAsset.ownership.owner.toString(), newOwner.publicKey.toBase58());
+  });
+
+  it("Burns a knowledge NFT so it can no longer be transferred", async () => {
+    const leafOwner = Keypair.generate();
+    const assetId = await mintLeaf(leafOwner.publicKey);
+
+    const leaf = await getLeafProof(assetId);
+    const tx = await program.methods
+      .burnKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    try {
+      await program.methods
+        .transferKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, maxRootAge)
+        .accounts({
+          treeConfig: tree_config,
+          leafOwner: leafOwner.publicKey,
+          leafDelegate: leafOwner.publicKey,
+          newLeafOwner: Keypair.generate().publicKey,
+          tree: tree.publicKey,
+        }).remainingAccounts(leaf.proof).signers([leafOwner])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Transfer of a burned leaf should fail");
+    } catch (err) {
+      assert.notInclude(err.toString(), "Transfer of a burned leaf should fail");
+    }
+  });
+
+  it("Rejects a wrong length proof with InvalidProofLength", async () => {
+    const leafOwner = Keypair.generate();
+    const assetId = await mintLeaf(leafOwner.publicKey);
+    const leaf = await getLeafProof(assetId);
+    const extraNode = { pubkey: Keypair.generate().publicKey, isSigner: false, isWritable: false };
+
+    for (const proof of [leaf.proof.slice(1), [...leaf.proof, extraNode]]) {
+      try {
+        await program.methods
+          .transferKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, maxRootAge)
+          .accounts({
+            treeConfig: tree_config,
+            leafOwner: leafOwner.publicKey,
+            leafDelegate: leafOwner.publicKey,
+            newLeafOwner: Keypair.generate().publicKey,
+            tree: tree.publicKey,
+          }).remainingAccounts(proof).signers([leafOwner])
+        .rpc({ commitment: 'confirmed' });
+        assert.fail("Transfer with a wrong length proof should fail");
+      } catch (err) {
+        assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "InvalidProofLength");
+      }
+    }
+
+    try {
+      await program.methods
+        .burnKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index)
+        .accounts({
+          treeConfig: tree_config,
+          leafOwner: leafOwner.publicKey,
+          leafDelegate: leafOwner.publicKey,
+          tree: tree.publicKey,
+        }).remainingAccounts(leaf.proof.slice(1)).signers([leafOwner])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Burn with a wrong length proof should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "InvalidProofLength");
+    }
+  });
+
+  it("Transfers from a tree with a depth 10 canopy using fewer proof accounts", async () => {
+    const { tree: canopyTree, treeConfig: canopyTreeConfig } = await createNewTree({ maxDepth: 14, maxBufferSize: 64 }, 10);
+    const [canopyTreeRegistry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), canopyTree.publicKey.toBuffer()],
+      program.programId
+    );
+    assert.strictEqual((await program.account.treeRegistry.fetch(canopyTreeRegistry)).canopyDepth, 10);
+
+    const leafOwner = Keypair.generate();
+    const mintTx = await program.methods
+      .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], Buffer.from([]), null, false)
+      .accounts({
+        payer: wallet.publicKey,
+        tree: canopyTree.publicKey,
+        treeConfig: canopyTreeConfig,
+        leafOwner: leafOwner.publicKey,
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(mintTx);
+
+    const [assetId] = findLeafAssetIdPda(umi, {
+      merkleTree: canopyTree.publicKey.toBase58() as UmiPK,
+      leafIndex: 0,
+    });
+    const leaf = await getLeafProof(assetId);
+    assert.strictEqual(leaf.proof.length, 14 - 10, "Only levels below the canopy need proof accounts");
+
+    const tx = await program.methods
+      .transferKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, maxRootAge)
+      .accounts({
+        treeConfig: canopyTreeConfig,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+        newLeafOwner: Keypair.generate().publicKey,
+        tree: canopyTree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+  });
+
+  it("Rejects a canopy deeper than the tree with InvalidCanopyDepth", async () => {
+    try {
+      await createNewTree({ maxDepth: 3, maxBufferSize: 8 }, 4);
+      assert.fail("Tree creation should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "InvalidCanopyDepth");
+    }
+  });
+
+  it("Freezes a knowledge NFT against transfer until it is thawed", async () => {
+    const leafOwner = Keypair.generate();
+    const assetId = await mintLeaf(leafOwner.publicKey);
+    const leaf = await getLeafProof(assetId);
+
+    const transfer = () => program.methods
+      .transferKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, maxRootAge)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+        newLeafOwner: Keypair.generate().publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+
+    const freezeTx = await program.methods
+      .freezeLeaf(leaf.nonce)
+      .accounts({
+        creator: wallet.publicKey,
+        tree: tree.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(freezeTx);
+
+    try {
+      await transfer();
+      assert.fail("Transfer of a frozen leaf should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "LeafFrozen");
+    }
+
+    const thawTx = await program.methods
+      .thawLeaf(leaf.nonce)
+      .accounts({
+        creator: wallet.publicKey,
+        tree: tree.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(thawTx);
+
+    await confirmTransaction(await transfer());
+  });
+
+  it("Transfers two knowledge NFTs to a new owner in one instruction", async () => {
+    const leafOwner = Keypair.generate();
+    const newOwner = Keypair.generate();
+    const assetIds = [await mintLeaf(leafOwner.publicKey), await mintLeaf(leafOwner.publicKey)];
+
+    // Proofs are fetched after both mints so they share the current root
+    const items = [];
+    const remainingAccounts = [];
+    for (const assetId of assetIds) {
+      const leaf = await getLeafProof(assetId);
+      const [frozenMarker] = PublicKey.findProgramAddressSync(
+        [Buffer.from("frozen"), tree.publicKey.toBuffer(), leaf.nonce.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      items.push({
+        root: leaf.root,
+        dataHash: leaf.dataHash,
+        creatorHash: leaf.creatorHash,
+        nonce: leaf.nonce,
+        index: leaf.index,
+      });
+      remainingAccounts.push({ pubkey: frozenMarker, isSigner: false, isWritable: false }, ...leaf.proof);
+    }
+
+    const tx = await program.methods
+      .transferBatch(items)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        newLeafOwner: newOwner.publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(remainingAccounts).signers([leafOwner])
+    .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    for (const assetId of assetIds) {
+      const rpcAsset = await umi.rpc.getAsset(assetId);
+      assert.strictEqual(rpcAsset.ownership.owner.toString(), newOwner.publicKey.toBase58());
+    }
+  });
+
+  it("Delegates a knowledge NFT and lets the delegate transfer it", async () => {
+    const leafOwner = Keypair.generate();
+    const delegate = Keypair.generate();
+    const newOwner = Keypair.generate();
+
+    const assetId = await mintLeaf(leafOwner.publicKey);
+
+    let leaf = await getLeafProof(assetId);
+    const delegateTx = await program.methods
+      .delegateKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        previousLeafDelegate: leafOwner.publicKey,
+        newLeafDelegate: delegate.publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(delegateTx);
+
+    // Re-delegating to the same delegate is a no-op
+    leaf = await getLeafProof(assetId);
+    const redelegateTx = await program.methods
+      .delegateKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        previousLeafDelegate: delegate.publicKey,
+        newLeafDelegate: delegate.publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(redelegateTx);
+
+    leaf = await getLeafProof(assetId);
+    const transferTx = await program.methods
+      .transferKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, maxRootAge)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: delegate.publicKey,
+        newLeafOwner: newOwner.publicKey,
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([delegate])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(transferTx);
+
+    const rpcAsset = await umi.rpc.getAsset(assetId);
+    assert.strictEqual(rpcAsset.ownership.owner.toString(), newOwner.publicKey.toBase58());
+  });
+
+  it("Delegates tree minting rights to a new signer", async () => {
+    const treeDelegate = Keypair.generate();
+
+    const tx = await program.methods
+      .setTreeOwner(Buffer.from([]))
+      .accounts({
+        creator: wallet.publicKey,
+        treeConfig: tree_config,
+        tree: tree.publicKey,
+        newTreeDelegate: treeDelegate.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    // The delegate mints directly through bubblegum
+    const delegateSigner = createSignerFromKeypair(
+      umi,
+      umi.eddsa.createKeypairFromSecretKey(treeDelegate.secretKey)
+    );
+    const leafOwner = Keypair.generate();
+    const mintTx = await mintV1(umi, {
+      leafOwner: leafOwner.publicKey.toBase58() as UmiPK,
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      treeCreatorOrDelegate: delegateSigner,
+      metadata: {
+        name: metadata.name,
+        symbol: metadata.symbol,
+        uri: metadata.uri,
+        sellerFeeBasisPoints: 0,
+        collection: null,
+        creators: [],
+      },
+    }).sendAndConfirm(umi);
+    await confirmTransaction(base58.deserialize(mintTx.signature)[0]);
+
+    treeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(
+      connection,
+      tree.publicKey
+    );
+    const rpcAsset = await umi.rpc.getAsset(
+      findLeafAssetIdPda(umi, {
+        merkleTree: tree.publicKey.toBase58() as UmiPK,
+        leafIndex: treeAccount.tree.rightMostPath.index - 1,
+      })[0]
+    );
+    assert.strictEqual(rpcAsset.ownership.owner.toString(), leafOwner.publicKey.toBase58());
+  });
+
+  it("Mints a knowledge NFT into a verified collection", async () => {
+    const knowledgeCollection = generateSigner(umi);
+    const createCollectionTx = await createNft(umi, {
+      mint: knowledgeCollection,
+      sellerFeeBasisPoints: percentAmount(0),
+      name: 'KNOWLEDGE-COLLECTION',
+      uri: "https://raw.githubusercontent.com/robertLam04/DEInference/main/example_task.json",
+      isCollection: true
+    }).sendAndConfirm(umi);
+    await confirmTransaction(base58.deserialize(createCollectionTx.signature)[0]);
+
+    const [knowledgeCollectionMetadata] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata", "utf8"),
+        new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID).toBuffer(),
+        new PublicKey(knowledgeCollection.publicKey).toBuffer(),
+      ],
+      new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID)
+    );
+
+    const [knowledgeCollectionEdition] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('metadata', 'utf8'),
+        new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID).toBuffer(),
+        new PublicKey(knowledgeCollection.publicKey).toBuffer(),
+        Buffer.from('edition', 'utf8'),
+      ],
+      new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID)
+    );
+
+    const leafOwner = Keypair.generate();
+    const tx = await program.methods
+      .mintToCollection(metadata.name, metadata.symbol, metadata.uri, 0, Buffer.from([]))
+      .accounts({
+        payer: wallet.publicKey,
+        treeConfig: tree_config,
+        tree: tree.publicKey,
+        leafOwner: leafOwner.publicKey,
+        collectionAuthority: wallet.publicKey,
+        collectionMint: knowledgeCollection.publicKey,
+        collectionMetadata: knowledgeCollectionMetadata,
+        collectionEdition: knowledgeCollectionEdition,
+        bubblegumSigner: bubblegumSigner,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    const [assetId] = findLeafAssetIdPda(umi, {
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      leafIndex: await getLeafIndex(tx),
+    });
+    const asset = await getAssetWithProof(umi, assetId);
+    const collection = unwrapOption(asset.metadata.collection);
+    assert.ok(collection, "Leaf should belong to a collection");
+    assert.strictEqual(collection.key.toString(), knowledgeCollection.publicKey.toString());
+    assert.ok(collection.verified, "Collection should be verified");
+  });
+
+  it("Requires collection membership of a leaf minted into a collection", async () => {
+    const knowledgeCollection = generateSigner(umi);
+    const createCollectionTx = await createNft(umi, {
+      mint: knowledgeCollection,
+      sellerFeeBasisPoints: percentAmount(0),
+      name: 'KNOWLEDGE-COLLECTION',
+      uri: "https://raw.githubusercontent.com/robertLam04/DEInference/main/example_task.json",
+      isCollection: true
+    }).sendAndConfirm(umi);
+    await confirmTransaction(base58.deserialize(createCollectionTx.signature)[0]);
+
+    const [knowledgeCollectionMetadata] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata", "utf8"),
+        new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID).toBuffer(),
+        new PublicKey(knowledgeCollection.publicKey).toBuffer(),
+      ],
+      new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID)
+    );
+    const [knowledgeCollectionEdition] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('metadata', 'utf8'),
+        new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID).toBuffer(),
+        new PublicKey(knowledgeCollection.publicKey).toBuffer(),
+        Buffer.from('edition', 'utf8'),
+      ],
+      new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID)
+    );
+
+    const leafOwner = Keypair.generate();
+    const tx = await program.methods
+      .mintToCollection(metadata.name, metadata.symbol, metadata.uri, 0, Buffer.from([]))
+      .accounts({
+        payer: wallet.publicKey,
+        treeConfig: tree_config,
+        tree: tree.publicKey,
+        leafOwner: leafOwner.publicKey,
+        collectionAuthority: wallet.publicKey,
+        collectionMint: knowledgeCollection.publicKey,
+        collectionMetadata: knowledgeCollectionMetadata,
+        collectionEdition: knowledgeCollectionEdition,
+        bubblegumSigner: bubblegumSigner,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    const [assetId] = findLeafAssetIdPda(umi, {
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      leafIndex: await getLeafIndex(tx),
+    });
+    const leaf = await getLeafProof(assetId);
+    // Bubblegum verifies the collection when minting into it
+    const leafMetadata = {
+      name: metadata.name,
+      symbol: metadata.symbol,
+      uri: metadata.uri,
+      sellerFeeBasisPoints: 0,
+      primarySaleHappened: false,
+      isMutable: false,
+      editionNonce: 0,
+      collection: { verified: true, key: new PublicKey(knowledgeCollection.publicKey) },
+      creators: [],
+    };
+
+    const requireMembership = (collectionMint: PublicKey) => program.methods
+      .requireCollectionMembership(leaf.root, leaf.nonce, leaf.index, leafMetadata, collectionMint)
+      .accounts({
+        treeConfig: tree_config,
+        tree: tree.publicKey,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+      }).remainingAccounts(leaf.proof)
+    .rpc({ commitment: 'confirmed' });
+
+    await confirmTransaction(await requireMembership(new PublicKey(knowledgeCollection.publicKey)));
+
+    try {
+      await requireMembership(Keypair.generate().publicKey);
+      assert.fail("Membership of another collection should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "NotInCollection");
+    }
+
+    // A config that is not the tree's authority means the tree is not a bubblegum tree
+    try {
+      await program.methods
+        .requireCollectionMembership(leaf.root, leaf.nonce, leaf.index, leafMetadata, new PublicKey(knowledgeCollection.publicKey))
+        .accounts({
+          treeConfig: Keypair.generate().publicKey,
+          tree: tree.publicKey,
+          leafOwner: leafOwner.publicKey,
+          leafDelegate: leafOwner.publicKey,
+        }).remainingAccounts(leaf.proof)
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Membership in a tree bubblegum does not own should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "InvalidTreeAccount");
+    }
+  });
+
+  it("Sets the size of an unsized collection after minting three into it", async () => {
+    // Created unsized, so the size can be set once
+    const knowledgeCollection = generateSigner(umi);
+    const createCollectionTx = await createNft(umi, {
+      mint: knowledgeCollection,
+      sellerFeeBasisPoints: percentAmount(0),
+      name: 'KNOWLEDGE-COLLECTION',
+      uri: "https://raw.githubusercontent.com/robertLam04/DEInference/main/example_task.json",
+    }).sendAndConfirm(umi);
+    await confirmTransaction(base58.deserialize(createCollectionTx.signature)[0]);
+
+    const [knowledgeCollectionMetadata] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata", "utf8"),
+        new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID).toBuffer(),
+        new PublicKey(knowledgeCollection.publicKey).toBuffer(),
+      ],
+      new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID)
+    );
+    const [knowledgeCollectionEdition] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('metadata', 'utf8'),
+        new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID).toBuffer(),
+        new PublicKey(knowledgeCollection.publicKey).toBuffer(),
+        Buffer.from('edition', 'utf8'),
+      ],
+      new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID)
+    );
+
+    const collectionMintedBefore = (await program.account.treeRegistry.fetch(treeRegistryPda)).collectionMinted.toNumber();
+    for (let i = 0; i < 3; i++) {
+      const tx = await program.methods
+        .mintToCollection(metadata.name, metadata.symbol, metadata.uri, 0, Buffer.from([]))
+        .accounts({
+          payer: wallet.publicKey,
+          treeConfig: tree_config,
+          tree: tree.publicKey,
+          leafOwner: Keypair.generate().publicKey,
+          collectionAuthority: wallet.publicKey,
+          collectionMint: knowledgeCollection.publicKey,
+          collectionMetadata: knowledgeCollectionMetadata,
+          collectionEdition: knowledgeCollectionEdition,
+          bubblegumSigner: bubblegumSigner,
+        }).signers([wallet.payer])
+      .rpc({ commitment: 'confirmed' });
+      await confirmTransaction(tx);
+    }
+    const registry = await program.account.treeRegistry.fetch(treeRegistryPda);
+    assert.strictEqual(registry.collectionMinted.toNumber(), collectionMintedBefore + 3);
+
+    const outsider = Keypair.generate();
+    try {
+      await program.methods
+        .setCollectionSize(new anchor.BN(3))
+        .accounts({
+          collectionAuthority: outsider.publicKey,
+          collectionMint: knowledgeCollection.publicKey,
+          collectionMetadata: knowledgeCollectionMetadata,
+        }).signers([outsider])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Only the collection update authority may set the size");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "Unauthorized");
+    }
+
+    const tx = await program.methods
+      .setCollectionSize(new anchor.BN(3))
+      .accounts({
+        collectionAuthority: wallet.publicKey,
+        collectionMint: knowledgeCollection.publicKey,
+        collectionMetadata: knowledgeCollectionMetadata,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    const collectionAsset = await fetchDigitalAsset(umi, knowledgeCollection.publicKey);
+    const details = unwrapOption(collectionAsset.metadata.collectionDetails);
+    assert.ok(details, "Collection should be sized");
+    assert.strictEqual(Number(details.size), 3);
+  });
+
+  it("Requires the noop log wrapper to create a tree", async () => {
+    const depthSizePair: ValidDepthSizePair = { maxDepth: 3, maxBufferSize: 8 };
+    const unloggedTree = Keypair.generate();
+    const allocTreeIx = await createAllocTreeIx(
+      connection,
+      unloggedTree.publicKey,
+      wallet.publicKey,
+      depthSizePair,
+      0
+    );
+    await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(allocTreeIx),
+      [wallet.payer, unloggedTree],
+      { commitment: 'confirmed' }
+    );
+    const [unloggedTreeConfig] = PublicKey.findProgramAddressSync(
+      [unloggedTree.publicKey.toBuffer()],
+      new PublicKey(MPL_BUBBLEGUM_PROGRAM_ID)
+    );
+
+    try {
+      await program.methods
+        .createTree(depthSizePair.maxDepth, depthSizePair.maxBufferSize, 0, Buffer.from([]))
+        .accountsPartial({
+          tree: unloggedTree.publicKey,
+          treeConfig: unloggedTreeConfig,
+          payer: wallet.publicKey,
+          logWrapper: anchor.web3.SystemProgram.programId,
+        }).signers([wallet.payer])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Tree creation without the noop program should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "InvalidProgramId");
+    }
+  });
+
+  it("Rejects a tree account larger than its canopy depth with TreeAccountSizeMismatch", async () => {
+    const depthSizePair: ValidDepthSizePair = { maxDepth: 3, maxBufferSize: 8 };
+    const oversizedTree = Keypair.generate();
+    // Allocated with a canopy of depth 1, then created as if it had none
+    const allocTreeIx = await createAllocTreeIx(
+      connection,
+      oversizedTree.publicKey,
+      wallet.publicKey,
+      depthSizePair,
+      1
+    );
+    await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(allocTreeIx),
+      [wallet.payer, oversizedTree],
+      { commitment: 'confirmed' }
+    );
+    const [oversizedTreeConfig] = PublicKey.findProgramAddressSync(
+      [oversizedTree.publicKey.toBuffer()],
+      new PublicKey(MPL_BUBBLEGUM_PROGRAM_ID)
+    );
+
+    try {
+      await program.methods
+        .createTree(depthSizePair.maxDepth, depthSizePair.maxBufferSize, 0, Buffer.from([]))
+        .accounts({
+          tree: oversizedTree.publicKey,
+          treeConfig: oversizedTreeConfig,
+          payer: wallet.publicKey,
+        }).signers([wallet.payer])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Tree creation with a mismatched canopy should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "TreeAccountSizeMismatch");
+    }
+  });
+
+  it("Treats a retried tree creation as a no-op", async () => {
+    const depthSizePair: ValidDepthSizePair = { maxDepth: 3, maxBufferSize: 8 };
+    const { tree: retriedTree, treeConfig: retriedTreeConfig } = await createNewTree(depthSizePair, 0);
+    const [retriedTreeRegistry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), retriedTree.publicKey.toBuffer()],
+      program.programId
+    );
+    const registryBefore = await program.account.treeRegistry.fetch(retriedTreeRegistry);
+    const treeBefore = await connection.getAccountInfo(retriedTree.publicKey);
+
+    const tx = await program.methods
+      .createTree(depthSizePair.maxDepth, depthSizePair.maxBufferSize, 0, Buffer.from([]))
+      .accounts({
+        tree: retriedTree.publicKey,
+        treeConfig: retriedTreeConfig,
+        payer: wallet.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    const registryAfter = await program.account.treeRegistry.fetch(retriedTreeRegistry);
+    assert.strictEqual(registryAfter.createdAt.toString(), registryBefore.createdAt.toString());
+    assert.strictEqual(registryAfter.numMinted.toNumber(), registryBefore.numMinted.toNumber());
+    assert.deepEqual((await connection.getAccountInfo(retriedTree.publicKey)).data, treeBefore.data, "Tree should be untouched");
+
+    const programState = await program.account.programState.fetch(programStatePda);
+    const entries = programState.trees.filter((info) => info.treeAddress.equals(retriedTree.publicKey));
+    assert.strictEqual(entries.length, 1, "Tree should be registered once");
+  });
+
+  it("Rejects an unsupported tree config with InvalidTreeConfig", async () => {
+    const invalidTree = Keypair.generate();
+    const allocTreeIx = await createAllocTreeIx(
+      connection,
+      invalidTree.publicKey,
+      wallet.publicKey,
+      maxDepthSizePair,
+      0
+    );
+    await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(allocTreeIx),
+      [wallet.payer, invalidTree],
+      { commitment: 'confirmed' }
+    );
+
+    const [invalidTreeConfig] = PublicKey.findProgramAddressSync(
+      [invalidTree.publicKey.toBuffer()],
+      new PublicKey(MPL_BUBBLEGUM_PROGRAM_ID)
+    );
+
+    try {
+      await program.methods
+        .createTree(maxDepthSizePair.maxDepth, maxDepthSizePair.maxBufferSize + 1, canopyDepth, Buffer.from([]))
+        .accounts({
+          tree: invalidTree.publicKey,
+          treeConfig: invalidTreeConfig,
+          payer: wallet.publicKey,
+        }).signers([wallet.payer])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Tree creation should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "InvalidTreeConfig");
+    }
+  });
+
+  it("Rejects a tree not owned by spl account compression with InvalidTreeAccount", async () => {
+    // A system account whose tree config pda is derived correctly, so only the owner check fails
+    const systemAccount = Keypair.generate();
+    const airdropTx = await connection.requestAirdrop(systemAccount.publicKey, LAMPORTS_PER_SOL);
+    await confirmTransaction(airdropTx);
+    const [systemAccountConfig] = PublicKey.findProgramAddressSync(
+      [systemAccount.publicKey.toBuffer()],
+      new PublicKey(MPL_BUBBLEGUM_PROGRAM_ID)
+    );
+    const leafOwner = Keypair.generate();
+
+    try {
+      await program.methods
+        .transferKnowledge(Array(32).fill(0), Array(32).fill(0), Array(32).fill(0), new anchor.BN(0), 0, maxRootAge)
+        .accounts({
+          treeConfig: systemAccountConfig,
+          leafOwner: leafOwner.publicKey,
+          leafDelegate: leafOwner.publicKey,
+          newLeafOwner: Keypair.generate().publicKey,
+          tree: systemAccount.publicKey,
+        }).signers([leafOwner]).rpc({ commitment: 'confirmed' });
+      assert.fail("Transfer should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "InvalidTreeAccount");
+    }
+
+    try {
+      await program.methods
+        .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], Buffer.from([]), null, false)
+        .accounts({
+          payer: wallet.publicKey,
+          tree: systemAccount.publicKey,
+          treeConfig: systemAccountConfig,
+          leafOwner: leafOwner.publicKey,
+        }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+      assert.fail("Mint should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "InvalidTreeAccount");
+    }
+
+    // A real tree paired with another tree's config is rejected the same way
+    try {
+      await program.methods
+        .burnKnowledge(Array(32).fill(0), Array(32).fill(0), Array(32).fill(0), new anchor.BN(0), 0)
+        .accounts({
+          treeConfig: systemAccountConfig,
+          leafOwner: leafOwner.publicKey,
+          leafDelegate: leafOwner.publicKey,
+          tree: tree.publicKey,
+        }).signers([leafOwner]).rpc({ commitment: 'confirmed' });
+      assert.fail("Burn should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "InvalidTreeAccount");
+    }
+  });
+
+  it("Rejects invalid metadata with UriTooLong and NameTooLong", async () => {
+    const leafOwner = Keypair.generate();
+
+    try {
+      await program.methods
+        .mintKnowledge(metadata.name, metadata.symbol, "a".repeat(201), 0, [], Buffer.from([]), null, false)
+        .accounts({
+          payer: wallet.publicKey,
+          tree: tree.publicKey,
+          treeConfig: tree_config,
+          leafOwner: leafOwner.publicKey
+        }).signers([wallet.payer]).rpc({ commitment: 'confirmed'});
+      assert.fail("Mint should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "UriTooLong");
+    }
+
+    try {
+      await program.methods
+        .mintKnowledge("a".repeat(33), metadata.symbol, metadata.uri, 0, [], Buffer.from([]), null, false)
+        .accounts({
+          payer: wallet.publicKey,
+          tree: tree.publicKey,
+          treeConfig: tree_config,
+          leafOwner: leafOwner.publicKey
+        }).signers([wallet.payer]).rpc({ commitment: 'confirmed'});
+      assert.fail("Mint should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "NameTooLong");
+    }
+  });
+
+  it("Rejects tree delegation from a non-creator with Unauthorized", async () => {
+    const notCreator = Keypair.generate();
+
+    try {
+      await program.methods
+        .setTreeOwner(Buffer.from([]))
+        .accounts({
+          creator: notCreator.publicKey,
+          treeConfig: tree_config,
+          tree: tree.publicKey,
+          newTreeDelegate: notCreator.publicKey,
+        }).signers([notCreator])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Delegation should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "Unauthorized");
+    }
+  });
+
+  it("Mints a full batch and rejects a batch exceeding tree capacity", async () => {
+    // Depth 3 tree holds exactly 8 leaves
+    const smallTree = await createNewTree({ maxDepth: 3, maxBufferSize: 8 }, 0);
+    const leafOwner = Keypair.generate();
+
+    const items = Array.from({ length: 8 }, (_, i) => ({
+      name: `${metadata.name}-${i}`,
+      symbol: metadata.symbol,
+      uri: metadata.uri,
+      sellerFeeBasisPoints: 0,
+      creators: [],
+    }));
+
+    const tx = await program.methods
+      .mintBatch(items, Buffer.from([]))
+      .accounts({
+        payer: wallet.publicKey,
+        treeConfig: smallTree.treeConfig,
+        tree: smallTree.tree.publicKey,
+        leafOwner: leafOwner.publicKey,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 })])
+      .signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    const [smallTreeRegistry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), smallTree.tree.publicKey.toBuffer()],
+      program.programId
+    );
+    let registry = await program.account.treeRegistry.fetch(smallTreeRegistry);
+    assert.strictEqual(registry.numMinted.toNumber(), 8);
+
+    try {
+      await program.methods
+        .mintBatch(items.slice(0, 1), Buffer.from([]))
+        .accounts({
+          payer: wallet.publicKey,
+          treeConfig: smallTree.treeConfig,
+          tree: smallTree.tree.publicKey,
+          leafOwner: leafOwner.publicKey,
+        }).signers([wallet.payer])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Batch should exceed tree capacity");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "TreeFull");
+    }
+
+    registry = await program.account.treeRegistry.fetch(smallTreeRegistry);
+    assert.strictEqual(registry.numMinted.toNumber(), 8, "No partial mints");
+  });
+
+  it("Redeems a knowledge NFT and cancels the redemption", async () => {
+    const leafOwner = Keypair.generate();
+    const airdropTx = await connection.requestAirdrop(leafOwner.publicKey, LAMPORTS_PER_SOL);
+    await confirmTransaction(airdropTx);
+
+    const assetId = await mintLeaf(leafOwner.publicKey);
+    const leaf = await getLeafProof(assetId);
+    const [voucher] = findVoucherPda(umi, {
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      nonce: leaf.nonce.toNumber(),
+    });
+
+    const redeemTx = await program.methods
+      .redeem(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+        tree: tree.publicKey,
+        voucher: voucher,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(redeemTx);
+    assert.ok(await connection.getAccountInfo(new PublicKey(voucher)), "Voucher should exist after redeem");
+
+    treeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, tree.publicKey);
+    const cancelTx = await program.methods
+      .cancelRedeem(Array.from(treeAccount.getCurrentRoot()))
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        tree: tree.publicKey,
+        voucher: voucher,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(cancelTx);
+    assert.isNull(await connection.getAccountInfo(new PublicKey(voucher)), "Voucher should be closed after cancel");
+  });
+
+  it("Rejects redeeming a frozen knowledge NFT with LeafFrozen", async () => {
+    const leafOwner = Keypair.generate();
+    const airdropTx = await connection.requestAirdrop(leafOwner.publicKey, LAMPORTS_PER_SOL);
+    await confirmTransaction(airdropTx);
+
+    const assetId = await mintLeaf(leafOwner.publicKey);
+    const leaf = await getLeafProof(assetId);
+    const [voucher] = findVoucherPda(umi, {
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      nonce: leaf.nonce.toNumber(),
+    });
+
+    const freezeTx = await program.methods
+      .freezeLeaf(leaf.nonce)
+      .accounts({
+        creator: wallet.publicKey,
+        tree: tree.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(freezeTx);
+
+    try {
+      await program.methods
+        .redeem(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index)
+        .accounts({
+          treeConfig: tree_config,
+          leafOwner: leafOwner.publicKey,
+          leafDelegate: leafOwner.publicKey,
+          tree: tree.publicKey,
+          voucher: voucher,
+        }).remainingAccounts(leaf.proof).signers([leafOwner])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Redeeming a frozen leaf should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "LeafFrozen");
+    }
+    assert.isNull(await connection.getAccountInfo(new PublicKey(voucher)), "No voucher should be created");
+  });
+
+  const setDecompressibleState = (creator: Keypair, state: { enabled: {} } | { disabled: {} }) => program.methods
+    .setDecompressibleState(state, Buffer.from([]))
+    .accounts({
+      creator: creator.publicKey,
+      treeConfig: tree_config,
+      tree: tree.publicKey,
+    }).signers([creator])
+  .rpc({ commitment: 'confirmed' });
+
+  // Bubblegum creates trees with decompression disabled, so this end to end test depends on
+  // set_decompressible_state to turn it on before redeeming
+  it("Redeems and decompresses a knowledge NFT into an spl token", async () => {
+    const leafOwner = Keypair.generate();
+    const airdropTx = await connection.requestAirdrop(leafOwner.publicKey, LAMPORTS_PER_SOL);
+    await confirmTransaction(airdropTx);
+
+    await confirmTransaction(await setDecompressibleState(wallet.payer, { enabled: {} }));
+
+    const assetId = await mintLeaf(leafOwner.publicKey);
+    const leaf = await getLeafProof(assetId);
+    const [voucher] = findVoucherPda(umi, {
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      nonce: leaf.nonce.toNumber(),
+    });
+
+    const redeemTx = await program.methods
+      .redeem(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+        tree: tree.publicKey,
+        voucher: voucher,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(redeemTx);
+
+    const mint = new PublicKey(assetId);
+    const [mintAuthority] = PublicKey.findProgramAddressSync(
+      [mint.toBuffer()],
+      new PublicKey(MPL_BUBBLEGUM_PROGRAM_ID)
+    );
+    const [metadataAccount] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata", "utf8"),
+        new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID).toBuffer(),
+        mint.toBuffer(),
+      ],
+      new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID)
+    );
+    const [masterEdition] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata", "utf8"),
+        new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID).toBuffer(),
+        mint.toBuffer(),
+        Buffer.from("edition", "utf8"),
+      ],
+      new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID)
+    );
+    const tokenAccount = anchor.utils.token.associatedAddress({ mint, owner: leafOwner.publicKey });
+    const [frozenMarker] = PublicKey.findProgramAddressSync(
+      [Buffer.from("frozen"), tree.publicKey.toBuffer(), leaf.nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    const decompressTx = await program.methods
+      .decompressV1({
+        name: metadata.name,
+        symbol: metadata.symbol,
+        uri: metadata.uri,
+        sellerFeeBasisPoints: 10,
+        primarySaleHappened: false,
+        isMutable: false,
+        editionNonce: 0,
+        collection: null,
+        creators: [],
+      })
+      .accounts({
+        voucher: voucher,
+        frozenMarker: frozenMarker,
+        leafOwner: leafOwner.publicKey,
+        tokenAccount: tokenAccount,
+        mint: mint,
+        mintAuthority: mintAuthority,
+        metadataAccount: metadataAccount,
+        masterEdition: masterEdition,
+      }).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(decompressTx);
+
+    const balance = await connection.getTokenAccountBalance(tokenAccount);
+    assert.strictEqual(balance.value.amount, "1");
+  });
+
+  it("Disables decompression so a knowledge NFT can no longer be decompressed", async () => {
+    try {
+      await setDecompressibleState(Keypair.generate(), { disabled: {} });
+      assert.fail("Only the tree creator may set the decompressible state");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "Unauthorized");
+    }
+
+    await confirmTransaction(await setDecompressibleState(wallet.payer, { disabled: {} }));
+
+    const leafOwner = Keypair.generate();
+    const assetId = await mintLeaf(leafOwner.publicKey);
+    const leaf = await getLeafProof(assetId);
+    const [voucher] = findVoucherPda(umi, {
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      nonce: leaf.nonce.toNumber(),
+    });
+
+    // Bubblegum checks the decompressible state when the leaf is redeemed for decompression
+    try {
+      await program.methods
+        .redeem(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index)
+        .accounts({
+          treeConfig: tree_config,
+          leafOwner: leafOwner.publicKey,
+          leafDelegate: leafOwner.publicKey,
+          tree: tree.publicKey,
+          voucher: voucher,
+        }).remainingAccounts(leaf.proof).signers([leafOwner])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Redeeming for decompression should fail while decompression is disabled");
+    } catch (err) {
+      assert.isTrue(
+        (err as anchor.web3.SendTransactionError).logs.some((log) => log.includes("DecompressionDisabled")),
+        "Bubblegum should reject the redeem with DecompressionDisabled"
+      );
+    }
+    assert.isNull(await connection.getAccountInfo(new PublicKey(voucher)), "No voucher should be created");
+
+    await confirmTransaction(await setDecompressibleState(wallet.payer, { enabled: {} }));
+  });
+
+  it("Mints leaves with royalties from a shared creators config", async () => {
+    const [creatorsConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("creators_config"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const configCreators = [
+      { address: Keypair.generate().publicKey, verified: false, share: 60 },
+      { address: Keypair.generate().publicKey, verified: false, share: 40 },
+    ];
+    const setTx = await program.methods
+      .setCreatorsConfig(250, configCreators)
+      .accounts({
+        authority: wallet.publicKey,
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(setTx);
+
+    const expectedCreatorHash = Array.from(hashMetadataCreators(configCreators.map((creator) => ({
+      ...creator,
+      address: creator.address.toBase58() as UmiPK,
+    }))));
+
+    // The inline seller fee and creators are replaced by the config's
+    const mintWithConfig = async () => {
+      const mintTx = await program.methods
+        .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], Buffer.from([]), null, false)
+        .accounts({
+          payer: wallet.publicKey,
+          tree: tree.publicKey,
+          treeConfig: tree_config,
+          leafOwner: Keypair.generate().publicKey,
+          creatorsConfig,
+        }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+      await confirmTransaction(mintTx);
+
+      const [assetId] = findLeafAssetIdPda(umi, {
+        merkleTree: tree.publicKey.toBase58() as UmiPK,
+        leafIndex: await getLeafIndex(mintTx),
+      });
+      return assetId;
+    }
+
+    const assetIds = [await mintWithConfig(), await mintWithConfig()];
+    for (const assetId of assetIds) {
+      const leaf = await getLeafProof(assetId);
+      assert.deepEqual(leaf.creatorHash, expectedCreatorHash);
+
+      const asset = await getAssetWithProof(umi, assetId);
+      assert.strictEqual(asset.metadata.sellerFeeBasisPoints, 250);
+      assert.strictEqual(asset.metadata.creators.length, 2);
+    }
+
+    // Updating the config leaves already minted leaves untouched
+    const updateTx = await program.methods
+      .setCreatorsConfig(500, [{ address: Keypair.generate().publicKey, verified: false, share: 100 }])
+      .accounts({
+        authority: wallet.publicKey,
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(updateTx);
+
+    for (const assetId of assetIds) {
+      const leaf = await getLeafProof(assetId);
+      assert.deepEqual(leaf.creatorHash, expectedCreatorHash);
+    }
+    const updatedLeaf = await getLeafProof(await mintWithConfig());
+    assert.notDeepEqual(updatedLeaf.creatorHash, expectedCreatorHash, "New mints should use the updated creators");
+  });
+
+  it("Rejects minting with another authority's creators config", async () => {
+    const outsider = Keypair.generate();
+    await confirmTransaction(await connection.requestAirdrop(outsider.publicKey, LAMPORTS_PER_SOL));
+    const [outsiderCreatorsConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("creators_config"), outsider.publicKey.toBuffer()],
+      program.programId
+    );
+    const setTx = await program.methods
+      .setCreatorsConfig(1000, [{ address: outsider.publicKey, verified: false, share: 100 }])
+      .accounts({
+        authority: outsider.publicKey,
+      }).signers([outsider]).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(setTx);
+
+    try {
+      await program.methods
+        .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], Buffer.from([]), null, false)
+        .accounts({
+          payer: wallet.publicKey,
+          tree: tree.publicKey,
+          treeConfig: tree_config,
+          leafOwner: Keypair.generate().publicKey,
+          creatorsConfig: outsiderCreatorsConfig,
+        }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+      assert.fail("Minting with an outsider's creators config should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "Unauthorized");
+    }
+  });
+
+  it("Computes the same leaf hashes as an actual mint", async () => {
+    const leafOwner = Keypair.generate();
+    const creators = [
+      { address: Keypair.generate().publicKey, verified: false, share: 70 },
+      { address: Keypair.generate().publicKey, verified: false, share: 30 },
+    ];
+    const mintTx = await program.methods
+      .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, creators, Buffer.from([]), null, false)
+      .accounts({
+        payer: wallet.publicKey,
+        tree: tree.publicKey,
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(mintTx);
+
+    const [assetId] = findLeafAssetIdPda(umi, {
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      leafIndex: await getLeafIndex(mintTx),
+    });
+    const leaf = await getLeafProof(assetId);
+    const leafMetadata = {
+      name: metadata.name,
+      symbol: metadata.symbol,
+      uri: metadata.uri,
+      sellerFeeBasisPoints: 10,
+      primarySaleHappened: false,
+      isMutable: false,
+      editionNonce: 0,
+      collection: null,
+      creators: creators,
+    };
+
+    const hashes = await program.methods
+      .computeLeafHashes(leafMetadata, leaf.nonce, leafOwner.publicKey, leafOwner.publicKey)
+      .accounts({
+        tree: tree.publicKey,
+      })
+      .view();
+
+    assert.deepEqual(hashes.dataHash, leaf.dataHash);
+    assert.deepEqual(hashes.creatorHash, leaf.creatorHash);
+    const leafHash = Array.from(new PublicKey((await umi.rpc.getAssetProof(assetId)).leaf).toBytes());
+    assert.deepEqual(hashes.leafHash, leafHash);
+  });
+
+  it("Verifies and unverifies a creator on a knowledge NFT", async () => {
+    const leafOwner = Keypair.generate();
+    const creator = Keypair.generate();
+    const creators = [{ address: creator.publicKey, verified: false, share: 100 }];
+
+    const mintTx = await program.methods
+      .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, creators, Buffer.from([]), null, false)
+      .accounts({
+        payer: wallet.publicKey,
+        tree: tree.publicKey,
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed'});
+    await confirmTransaction(mintTx);
+
+    const [assetId] = findLeafAssetIdPda(umi, {
+      merkleTree: tree.publicKey.toBase58() as UmiPK,
+      leafIndex: await getLeafIndex(mintTx),
+    });
+    const leafMetadata = {
+      name: metadata.name,
+      symbol: metadata.symbol,
+      uri: metadata.uri,
+      sellerFeeBasisPoints: 10,
+      primarySaleHappened: false,
+      isMutable: false,
+      editionNonce: 0,
+      collection: null,
+      creators: creators,
+    };
+
+    let leaf = await getLeafProof(assetId);
+    const unverifiedCreatorHash = leaf.creatorHash;
+    const verifyTx = await program.methods
+      .verifyCreator(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, leafMetadata)
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+        tree: tree.publicKey,
+        payer: wallet.publicKey,
+        creator: creator.publicKey,
+      }).remainingAccounts(leaf.proof).signers([wallet.payer, creator])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(verifyTx);
+
+    leaf = await getLeafProof(assetId);
+    assert.notDeepEqual(leaf.creatorHash, unverifiedCreatorHash, "Creator hash should change once verified");
+    const asset = await getAssetWithProof(umi, assetId);
+    assert.ok(asset.metadata.creators[0].verified, "Creator should be verified");
+
+    const unverifyTx = await program.methods
+      .unverifyCreator(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, {
+        ...leafMetadata,
+        creators: [{ ...creators[0], verified: true }],
+      })
+      .accounts({
+        treeConfig: tree_config,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+        tree: tree.publicKey,
+        payer: wallet.publicKey,
+        creator: creator.publicKey,
+      }).remainingAccounts(leaf.proof).signers([wallet.payer, creator])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(unverifyTx);
+
+    leaf = await getLeafProof(assetId);
+    assert.deepEqual(leaf.creatorHash, unverifiedCreatorHash, "Creator hash should match the unverified hash again");
+  });
+
+  it("Rejects verifying a creator missing from the metadata with CreatorNotFound", async () => {
+    const leafOwner = Keypair.generate();
+    const notCreator = Keypair.generate();
+    const assetId = await mintLeaf(leafOwner.publicKey);
+    const leaf = await getLeafProof(assetId);
+
+    try {
+      await program.methods
+        .verifyCreator(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index, {
+          name: metadata.name,
+          symbol: metadata.symbol,
+          uri: metadata.uri,
+          sellerFeeBasisPoints: 10,
+          primarySaleHappened: false,
+          isMutable: false,
+          editionNonce: 0,
+          collection: null,
+          creators: [],
+        })
+        .accounts({
+          treeConfig: tree_config,
+          leafOwner: leafOwner.publicKey,
+          leafDelegate: leafOwner.publicKey,
+          tree: tree.publicKey,
+          payer: wallet.publicKey,
+          creator: notCreator.publicKey,
+        }).remainingAccounts(leaf.proof).signers([wallet.payer, notCreator])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Verification should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "CreatorNotFound");
+    }
+  });
+
+  it("Updates the uri of a mutable knowledge NFT", async () => {
+    const { tree: mutableTree, treeConfig: mutableTreeConfig } = await createNewTree({ maxDepth: 3, maxBufferSize: 8 }, 0);
+    const treeDelegate = Keypair.generate();
+
+    const delegateTx = await program.methods
+      .setTreeOwner(Buffer.from([]))
+      .accounts({
+        creator: wallet.publicKey,
+        treeConfig: mutableTreeConfig,
+        tree: mutableTree.publicKey,
+        newTreeDelegate: treeDelegate.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(delegateTx);
+
+    const leafOwner = Keypair.generate();
+    const mintTx = await program.methods
+      .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 0, [], Buffer.from([]), null, true)
+      .accounts({
+        payer: wallet.publicKey,
+        tree: mutableTree.publicKey,
+        treeConfig: mutableTreeConfig,
+        leafOwner: leafOwner.publicKey,
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(mintTx);
+
+    const [assetId] = findLeafAssetIdPda(umi, {
+      merkleTree: mutableTree.publicKey.toBase58() as UmiPK,
+      leafIndex: 0,
+    });
+    const leaf = await getLeafProof(assetId);
+    const currentMetadata = {
+      name: metadata.name,
+      symbol: metadata.symbol,
+      uri: metadata.uri,
+      sellerFeeBasisPoints: 0,
+      primarySaleHappened: false,
+      isMutable: true,
+      editionNonce: 0,
+      collection: null,
+      creators: [],
+    };
+    const newUri = "https://example.com/knowledge-v2.json";
+
+    const tx = await program.methods
+      .updateMetadata(leaf.root, leaf.nonce, leaf.index, currentMetadata, {
+        name: null,
+        uri: newUri,
+        sellerFeeBasisPoints: null,
+      })
+      .accounts({
+        treeConfig: mutableTreeConfig,
+        authority: treeDelegate.publicKey,
+        collectionMint: null,
+        collectionMetadata: null,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+        payer: wallet.publicKey,
+        tree: mutableTree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([wallet.payer, treeDelegate])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    const updated = await getLeafProof(assetId);
+    const expectedDataHash = Array.from(hashMetadataData({ ...currentMetadata, uri: newUri }));
+    assert.deepEqual(updated.dataHash, expectedDataHash, "Data hash should reflect the new uri");
+    assert.notDeepEqual(updated.dataHash, leaf.dataHash);
+  });
+
+  it("Rejects updating an immutable knowledge NFT with ImmutableMetadata", async () => {
+    const leafOwner = Keypair.generate();
+    const assetId = await mintLeaf(leafOwner.publicKey);
+    const leaf = await getLeafProof(assetId);
+
+    try {
+      await program.methods
+        .updateMetadata(leaf.root, leaf.nonce, leaf.index, {
+          name: metadata.name,
+          symbol: metadata.symbol,
+          uri: metadata.uri,
+          sellerFeeBasisPoints: 10,
+          primarySaleHappened: false,
+          isMutable: false,
+          editionNonce: 0,
+          collection: null,
+          creators: [],
+        }, {
+          name: null,
+          uri: "https://example.com/knowledge-v2.json",
+          sellerFeeBasisPoints: null,
+        })
+        .accounts({
+          treeConfig: tree_config,
+          authority: wallet.publicKey,
+          collectionMint: null,
+          collectionMetadata: null,
+          leafOwner: leafOwner.publicKey,
+          leafDelegate: leafOwner.publicKey,
+          payer: wallet.publicKey,
+          tree: tree.publicKey,
+        }).remainingAccounts(leaf.proof).signers([wallet.payer])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Update should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "ImmutableMetadata");
+    }
+  });
+
+  it("Verifies a leaf proof and rejects a tampered leaf", async () => {
+    const leafOwner = Keypair.generate();
+    const assetId = await mintLeaf(leafOwner.publicKey);
+    const leaf = await getLeafProof(assetId);
+    const leafHash = Array.from(new PublicKey((await umi.rpc.getAssetProof(assetId)).leaf).toBytes());
+
+    const tx = await program.methods
+      .verifyLeaf(leaf.root, leafHash, leaf.index)
+      .accounts({
+        tree: tree.publicKey,
+      }).remainingAccounts(leaf.proof)
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    const tamperedLeaf = [...leafHash];
+    tamperedLeaf[0] ^= 1;
+    try {
+      await program.methods
+        .verifyLeaf(leaf.root, tamperedLeaf, leaf.index)
+        .accounts({
+          tree: tree.publicKey,
+        }).remainingAccounts(leaf.proof)
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Verification of a tampered leaf should fail");
+    } catch (err) {
+      assert.include(err.toString(), "Error", "Compression program should reject the proof");
+      assert.notInclude(err.toString(), "Verification of a tampered leaf should fail");
+    }
+  });
+
+  it("Creates and mints to trees owned by distinct role authorities", async () => {
+    for (const role of [Buffer.from("minter"), Buffer.from("burner")]) {
+      const { tree: roleTree, treeConfig: roleTreeConfig } = await createNewTree({ maxDepth: 3, maxBufferSize: 8 }, 0, role);
+      const [roleAuthority] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tree_owner"), role, roleTree.publicKey.toBuffer()],
+        program.programId
+      );
+      const treeConfigData = await fetchTreeConfigFromSeeds(umi, {
+        merkleTree: roleTree.publicKey.toBase58() as UmiPK,
+      });
+      assert.strictEqual(treeConfigData.treeCreator.toString(), roleAuthority.toBase58());
+
+      const leafOwner = Keypair.generate();
+      const tx = await program.methods
+        .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], role, null, false)
+        .accounts({
+          payer: wallet.publicKey,
+          tree: roleTree.publicKey,
+          treeConfig: roleTreeConfig,
+          leafOwner: leafOwner.publicKey,
+        }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+      await confirmTransaction(tx);
+
+      // Batch mints sign with the same role authority
+      const batchTx = await program.methods
+        .mintBatch([{ name: metadata.name, symbol: metadata.symbol, uri: metadata.uri, sellerFeeBasisPoints: 0, creators: [] }], role)
+        .accounts({
+          payer: wallet.publicKey,
+          treeConfig: roleTreeConfig,
+          tree: roleTree.publicKey,
+          leafOwner: leafOwner.publicKey,
+        }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+      await confirmTransaction(batchTx);
+
+      const roleTreeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, roleTree.publicKey);
+      assert.strictEqual(roleTreeAccount.tree.rightMostPath.index, 2);
+
+      // The default tree_owner pda is not the creator of a role tree
+      try {
+        await program.methods
+          .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], Buffer.from([]), null, false)
+          .accounts({
+            payer: wallet.publicKey,
+            tree: roleTree.publicKey,
+            treeConfig: roleTreeConfig,
+            leafOwner: leafOwner.publicKey,
+          }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+        assert.fail("Mint with the wrong role should fail");
+      } catch (err) {
+        assert.notInclude(err.toString(), "Mint with the wrong role should fail");
+      }
+    }
+  });
+
+  it("Rejects a role longer than a pda seed with InvalidAuthoritySeeds", async () => {
+    const depthSizePair: ValidDepthSizePair = { maxDepth: 3, maxBufferSize: 8 };
+    const oversizedRoleTree = Keypair.generate();
+    const allocTreeIx = await createAllocTreeIx(
+      connection,
+      oversizedRoleTree.publicKey,
+      wallet.publicKey,
+      depthSizePair,
+      0
+    );
+    await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(allocTreeIx),
+      [wallet.payer, oversizedRoleTree],
+      { commitment: 'confirmed' }
+    );
+    const [oversizedRoleTreeConfig] = PublicKey.findProgramAddressSync(
+      [oversizedRoleTree.publicKey.toBuffer()],
+      new PublicKey(MPL_BUBBLEGUM_PROGRAM_ID)
+    );
+
+    // The client cannot derive the tree owner for such a role either, so any address is passed
+    try {
+      await program.methods
+        .createTree(depthSizePair.maxDepth, depthSizePair.maxBufferSize, 0, Buffer.alloc(33, 1))
+        .accountsPartial({
+          tree: oversizedRoleTree.publicKey,
+          treeConfig: oversizedRoleTreeConfig,
+          payer: wallet.publicKey,
+          treeOwner: Keypair.generate().publicKey,
+        }).signers([wallet.payer])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Tree creation with an oversized role should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "InvalidAuthoritySeeds");
+    }
+  });
+
+  it("Appends raw leaves to a program owned tree", async () => {
+    const leafTree = Keypair.generate();
+    const depthSizePair: ValidDepthSizePair = { maxDepth: 3, maxBufferSize: 8 };
+    const allocTreeIx = await createAllocTreeIx(
+      connection,
+      leafTree.publicKey,
+      wallet.publicKey,
+      depthSizePair,
+      0
+    );
+    await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(allocTreeIx),
+      [wallet.payer, leafTree],
+      { commitment: 'confirmed' }
+    );
+
+    const createTx = await program.methods
+      .createLeafTree(depthSizePair.maxDepth, depthSizePair.maxBufferSize)
+      .accounts({
+        tree: leafTree.publicKey,
+        creator: wallet.publicKey,
+      }).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(createTx);
+
+    let leafTreeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, leafTree.publicKey);
+    let previousRoot = leafTreeAccount.getCurrentRoot();
+
+    for (const fill of [1, 2]) {
+      const tx = await program.methods
+        .appendLeaf(Array(32).fill(fill))
+        .accounts({
+          tree: leafTree.publicKey,
+          creator: wallet.publicKey,
+        }).rpc({ commitment: 'confirmed' });
+      await confirmTransaction(tx);
+
+      leafTreeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, leafTree.publicKey);
+      const root = leafTreeAccount.getCurrentRoot();
+      assert.notDeepEqual(root, previousRoot, "Root should change after each append");
+      previousRoot = root;
+    }
+    assert.strictEqual(leafTreeAccount.tree.rightMostPath.index, 2);
+
+    const outsider = Keypair.generate();
+    try {
+      await program.methods
+        .appendLeaf(Array(32).fill(3))
+        .accounts({
+          tree: leafTree.publicKey,
+          creator: outsider.publicKey,
+        }).signers([outsider])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Only the tree creator may append");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "Unauthorized");
+    }
+  });
+
+  it("Appends a leaf only when the new root matches the expected root", async () => {
+    const leafTree = Keypair.generate();
+    const depthSizePair: ValidDepthSizePair = { maxDepth: 3, maxBufferSize: 8 };
+    const allocTreeIx = await createAllocTreeIx(
+      connection,
+      leafTree.publicKey,
+      wallet.publicKey,
+      depthSizePair,
+      0
+    );
+    await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(allocTreeIx),
+      [wallet.payer, leafTree],
+      { commitment: 'confirmed' }
+    );
+
+    const createTx = await program.methods
+      .createLeafTree(depthSizePair.maxDepth, depthSizePair.maxBufferSize)
+      .accounts({
+        tree: leafTree.publicKey,
+        creator: wallet.publicKey,
+      }).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(createTx);
+
+    let leafTreeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, leafTree.publicKey);
+    const staleRoot = leafTreeAccount.getCurrentRoot();
+
+    // The root before the append can never be the root after it
+    const leaf = Buffer.alloc(32, 1);
+    try {
+      await program.methods
+        .appendAndCheck(Array.from(leaf), Array.from(staleRoot))
+        .accounts({
+          tree: leafTree.publicKey,
+          creator: wallet.publicKey,
+        }).rpc({ commitment: 'confirmed' });
+      assert.fail("Append with a stale expected root should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "RootMismatch");
+    }
+
+    leafTreeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, leafTree.publicKey);
+    assert.strictEqual(leafTreeAccount.tree.rightMostPath.index, 0, "Failed append should be rolled back");
+    assert.deepEqual(leafTreeAccount.getCurrentRoot(), staleRoot);
+
+    const leaves = [leaf, ...Array.from({ length: 7 }, () => Buffer.alloc(32))];
+    const expectedRoot = new MerkleTree(leaves).root;
+    const tx = await program.methods
+      .appendAndCheck(Array.from(leaf), Array.from(expectedRoot))
+      .accounts({
+        tree: leafTree.publicKey,
+        creator: wallet.publicKey,
+      }).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
+
+    leafTreeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, leafTree.publicKey);
+    assert.strictEqual(leafTreeAccount.tree.rightMostPath.index, 1);
+    assert.deepEqual(leafTreeAccount.getCurrentRoot(), expectedRoot);
+  });
+
+  it("Closes an empty tree and reclaims its registry rent", async () => {
+    const { tree: closingTree, treeConfig: closingTreeConfig } = await createNewTree({ maxDepth: 3, maxBufferSize: 8 }, 0);
+    const [closingTreeRegistry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), closingTree.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const leafOwner = Keypair.generate();
+    const mintTx = await program.methods
+      .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], Buffer.from([]), null, false)
+      .accounts({
+        payer: wallet.publicKey,
+        tree: closingTree.publicKey,
+        treeConfig: closingTreeConfig,
+        leafOwner: leafOwner.publicKey,
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(mintTx);
+
+    const [assetId] = findLeafAssetIdPda(umi, {
+      merkleTree: closingTree.publicKey.toBase58() as UmiPK,
+      leafIndex: 0,
+    });
+    const leaf = await getLeafProof(assetId);
+    const burnTx = await program.methods
+      .burnKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index)
+      .accounts({
+        treeConfig: closingTreeConfig,
+        leafOwner: leafOwner.publicKey,
+        leafDelegate: leafOwner.publicKey,
+        tree: closingTree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([leafOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(burnTx);
+
+    const destination = Keypair.generate();
+    const [closingMintAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_auth"), closingTree.publicKey.toBuffer()],
+      program.programId
+    );
+    const reclaimedLamports = await connection.getBalance(closingTreeRegistry) + await connection.getBalance(closingMintAuthority);
+    const closeTx = await program.methods
+      .closeTree(Buffer.from([]))
+      .accounts({
+        creator: wallet.publicKey,
+        treeConfig: closingTreeConfig,
+        tree: closingTree.publicKey,
+        destination: destination.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(closeTx);
+
+    assert.isNull(await connection.getAccountInfo(closingTreeRegistry), "Registry should be closed");
+    assert.isNull(await connection.getAccountInfo(closingMintAuthority), "Mint allowlist should be closed");
+    assert.strictEqual(await connection.getBalance(destination.publicKey), reclaimedLamports);
+    const programState = await program.account.programState.fetch(programStatePda);
+    assert.isFalse(
+      programState.trees.some((info) => info.treeAddress.equals(closingTree.publicKey)),
+      "Closed tree should be removed from program state"
+    );
+
+    // Retrying the creation must not recreate the registry and allowlist as empty accounts
+    try {
+      await program.methods
+        .createTree(3, 8, 0, Buffer.from([]))
+        .accounts({
+          tree: closingTree.publicKey,
+          treeConfig: closingTreeConfig,
+          payer: wallet.publicKey,
+        }).signers([wallet.payer])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Retrying the creation of a closed tree should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "TreeNotFound");
+    }
+    assert.isNull(await connection.getAccountInfo(closingTreeRegistry), "Registry should stay closed");
+  });
+
+  it("Rejects closing a tree with active leaves with TreeNotEmpty", async () => {
+    await mintLeaf(Keypair.generate().publicKey);
+
+    try {
+      await program.methods
+        .closeTree(Buffer.from([]))
+        .accounts({
+          creator: wallet.publicKey,
+          treeConfig: tree_config,
+          tree: tree.publicKey,
+          destination: wallet.publicKey,
+        }).signers([wallet.payer])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Close should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "TreeNotEmpty");
+    }
+  });
+
+  it("Restricts minting to allowlisted minters", async () => {
+    const minter = Keypair.generate();
+    const outsider = Keypair.generate();
+    for (const signer of [minter, outsider]) {
+      const airdrop = await connection.requestAirdrop(signer.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await confirmTransaction(airdrop);
+    }
+
+    const mintAs = (payer: Keypair) => program.methods
+      .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], Buffer.from([]), null, false)
+      .accounts({
+        payer: payer.publicKey,
+        tree: tree.publicKey,
+        treeConfig: tree_config,
+        leafOwner: Keypair.generate().publicKey,
+      }).signers([payer]).rpc({ commitment: 'confirmed' });
+
+    const addTx = await program.methods
+      .addMinter(minter.publicKey)
+      .accounts({
+        creator: wallet.publicKey,
+        tree: tree.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(addTx);
+
+    await confirmTransaction(await mintAs(minter));
+
+    try {
+      await mintAs(outsider);
+      assert.fail("Mint by a non-listed signer should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "Unauthorized");
+    }
+
+    const removeTx = await program.methods
+      .removeMinter(minter.publicKey)
+      .accounts({
+        creator: wallet.publicKey,
+        tree: tree.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(removeTx);
+
+    try {
+      await mintAs(minter);
+      assert.fail("Mint by a removed minter should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "Unauthorized");
+    }
+  });
+
+  it("Creates a tree with an external creator that mints directly", async () => {
+    const externalTree = Keypair.generate();
+    const externalCreator = Keypair.generate();
+    const depthSizePair: ValidDepthSizePair = { maxDepth: 3, maxBufferSize: 8 };
+    const allocTreeIx = await createAllocTreeIx(
+      connection,
+      externalTree.publicKey,
+      wallet.publicKey,
+      depthSizePair,
+      0
+    );
+    await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(allocTreeIx),
+      [wallet.payer, externalTree],
+      { commitment: 'confirmed' }
+    );
+
+    const [externalTreeConfig] = PublicKey.findProgramAddressSync(
+      [externalTree.publicKey.toBuffer()],
+      new PublicKey(MPL_BUBBLEGUM_PROGRAM_ID)
+    );
+    const createTx = await program.methods
+      .createTreeWithCreator(depthSizePair.maxDepth, depthSizePair.maxBufferSize, 0)
+      .accounts({
+        tree: externalTree.publicKey,
+        treeConfig: externalTreeConfig,
+        payer: wallet.publicKey,
+        treeCreator: externalCreator.publicKey,
+      }).signers([wallet.payer, externalCreator])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(createTx);
+
+    const treeConfigData = await fetchTreeConfigFromSeeds(umi, {
+      merkleTree: externalTree.publicKey.toBase58() as UmiPK,
+    });
+    assert.strictEqual(treeConfigData.treeCreator.toString(), externalCreator.publicKey.toBase58());
+
+    const creatorSigner = createSignerFromKeypair(
+      umi,
+      umi.eddsa.createKeypairFromSecretKey(externalCreator.secretKey)
+    );
+    const mintTx = await mintV1(umi, {
+      leafOwner: Keypair.generate().publicKey.toBase58() as UmiPK,
+      merkleTree: externalTree.publicKey.toBase58() as UmiPK,
+      treeCreatorOrDelegate: creatorSigner,
+      metadata: {
+        name: metadata.name,
+        symbol: metadata.symbol,
+        uri: metadata.uri,
+        sellerFeeBasisPoints: 0,
+        collection: null,
+        creators: [],
+      },
+    }).sendAndConfirm(umi);
+    await confirmTransaction(base58.deserialize(mintTx.signature)[0]);
+
+    const externalTreeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, externalTree.publicKey);
+    assert.strictEqual(externalTreeAccount.tree.rightMostPath.index, 1);
+  });
+
+  it("Reports the tree_owner pda as the authority of a program created tree", async () => {
+    // A fresh tree, since other tests delegate the shared tree away from its pda
+    const { tree: authorityTree, treeConfig: authorityTreeConfig } = await createNewTree({ maxDepth: 3, maxBufferSize: 8 }, 0);
+    const [authorityTreeOwner] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tree_owner"), authorityTree.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const authority = await program.methods
+      .getTreeAuthority(Buffer.from([]))
       .accounts({
-        pdaAccount: requestStatePda,
-        receiver: wallet.payer.publicKey
-      }).signers([wallet.payer]).rpc({ commitment: 'confirmed'});
-      console.log("Request state account closed:", closeRequestStateAccountTx);
-    } 
-    
-    // Give tree account space
+        treeConfig: authorityTreeConfig,
+        tree: authorityTree.publicKey,
+      })
+      .view();
+
+    assert.strictEqual(authority.treeDelegate.toBase58(), authorityTreeOwner.toBase58());
+    assert.isTrue(authority.isTreeOwner);
+  });
+
+  it("Creates a tree through cpi from a parent program pda", async () => {
+    const governedTree = Keypair.generate();
+    const depthSizePair: ValidDepthSizePair = { maxDepth: 3, maxBufferSize: 8 };
     const allocTreeIx = await createAllocTreeIx(
       connection,
-      tree.publicKey,
+      governedTree.publicKey,
       wallet.publicKey,
-      maxDepthSizePair,
-      canopyDepth
+      depthSizePair,
+      0
     );
-
-    const tx = new Transaction().add(allocTreeIx);
-    const txSignature = await sendAndConfirmTransaction(
+    await sendAndConfirmTransaction(
       connection,
-      tx,
-      [wallet.payer, tree],
-      {
-        commitment: 'confirmed',
-      }
+      new Transaction().add(allocTreeIx),
+      [wallet.payer, governedTree],
+      { commitment: 'confirmed' }
     );
 
-    console.log('Tree Address:', tree.publicKey.toBase58());
-
-    // Create collection mint
-    const createNftTx = await createNft(umi, {
-      mint: collection_mint,
-      sellerFeeBasisPoints: percentAmount(0),
-      name: 'TEST-COLLECTION',
-      uri: "https://raw.githubusercontent.com/robertLam04/DEInference/main/example_task.json",
-      isCollection: true
-    }).sendAndConfirm(umi);
-    
-    const createNftSignature = base58.deserialize(createNftTx.signature)[0];
-    console.log(`https://explorer.solana.com/tx/${createNftSignature}?cluster=devnet`)
+    const [governedTreeConfig] = PublicKey.findProgramAddressSync(
+      [governedTree.publicKey.toBuffer()],
+      new PublicKey(MPL_BUBBLEGUM_PROGRAM_ID)
+    );
+    const [governance] = PublicKey.findProgramAddressSync([Buffer.from("governance")], mockParent.programId);
+    const [governedTreeOwner] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tree_owner"), governedTree.publicKey.toBuffer()],
+      program.programId
+    );
+    const [governedRegistry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), governedTree.publicKey.toBuffer()],
+      program.programId
+    );
+    const [governedMintAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_auth"), governedTree.publicKey.toBuffer()],
+      program.programId
+    );
+    const [programState] = PublicKey.findProgramAddressSync([Buffer.from("knowledge")], program.programId);
 
-    await confirmTransaction(createNftSignature);
+    const tx = await mockParent.methods
+      .createGovernedTree(depthSizePair.maxDepth, depthSizePair.maxBufferSize)
+      .accounts({
+        tree: governedTree.publicKey,
+        treeConfig: governedTreeConfig,
+        payer: wallet.publicKey,
+        parentProgram: mockParent.programId,
+        treeOwner: governedTreeOwner,
+        programState,
+        treeRegistry: governedRegistry,
+        mintAuthority: governedMintAuthority,
+        config: configPda,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
 
-    const updateNftUpdateAuthTx = await updateV1(umi, {
-      mint: collection_mint.publicKey,
-      newUpdateAuthority: tree_owner.toBase58() as UmiPK
-    }).sendAndConfirm(umi);
+    // The tree_owner pda creates the tree, while the parent pda is recorded as its only minter
+    const treeConfigData = await fetchTreeConfigFromSeeds(umi, {
+      merkleTree: governedTree.publicKey.toBase58() as UmiPK,
+    });
+    assert.strictEqual(treeConfigData.treeCreator.toString(), governedTreeOwner.toBase58());
 
-    const updateNftUpdateAuthSignature = base58.deserialize(updateNftUpdateAuthTx.signature)[0];
-    console.log(`https://explorer.solana.com/tx/${updateNftUpdateAuthSignature}?cluster=devnet`)
+    const registry = await program.account.treeRegistry.fetch(governedRegistry);
+    assert.strictEqual(registry.creator.toBase58(), governance.toBase58());
 
-    // Derive collection metadata pda account
-    collectionMetadataAccount = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("metadata", "utf8"),
-        new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID).toBuffer(),
-        new PublicKey(collection_mint.publicKey).toBuffer(),
-      ],
-      new PublicKey(MPL_TOKEN_METADATA_PROGRAM_ID)
-    )[0];
+    const mintAuthority = await program.account.mintAuthority.fetch(governedMintAuthority);
+    assert.deepEqual(mintAuthority.minters.map((minter) => minter.toBase58()), [governance.toBase58()]);
   });
 
-  it.only("Initializes the program state pda", async () => {
-    
-    // Call the initialize function
-    const tx = await program.methods.initialize()
-    .accounts({
-      payer: wallet.publicKey,
-    })
-    .signers([wallet.payer])
-    .rpc({commitment: 'confirmed'});
+  it("Migrates a tree from an old owner and mints under the tree_owner pda", async () => {
+    const legacyTree = Keypair.generate();
+    const oldOwner = Keypair.generate();
+    const airdrop = await connection.requestAirdrop(oldOwner.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await confirmTransaction(airdrop);
 
-    // Fetch the tree_state account
-    programStateAccountInfo = await provider.connection.getAccountInfo(programStatePda);
-    programStateData = await program.account.programState.fetch(programStatePda);
+    const depthSizePair: ValidDepthSizePair = { maxDepth: 3, maxBufferSize: 8 };
+    const allocTreeIx = await createAllocTreeIx(
+      connection,
+      legacyTree.publicKey,
+      wallet.publicKey,
+      depthSizePair,
+      0
+    );
+    await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(allocTreeIx),
+      [wallet.payer, legacyTree],
+      { commitment: 'confirmed' }
+    );
 
-    assert.strictEqual(
-      programStateAccountInfo.owner.toString(),
-      program.programId.toString(),
-      "tree_state account is not owned by the program"
+    // A tree whose creator is not derived from the current tree_owner seeds
+    const [legacyTreeConfig] = PublicKey.findProgramAddressSync(
+      [legacyTree.publicKey.toBuffer()],
+      new PublicKey(MPL_BUBBLEGUM_PROGRAM_ID)
     );
+    const createTx = await program.methods
+      .createTreeWithCreator(depthSizePair.maxDepth, depthSizePair.maxBufferSize, 0)
+      .accounts({
+        tree: legacyTree.publicKey,
+        treeConfig: legacyTreeConfig,
+        payer: wallet.publicKey,
+        treeCreator: oldOwner.publicKey,
+      }).signers([wallet.payer, oldOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(createTx);
 
-    assert.strictEqual(
-      programStateData.creator.toString(),
-      "2gUrmvYsLTpXB5VwjP2ZpXD4kY4HWRP89aDzQQ7TKbwh",
-      "unexpected creator");
+    const migrateTx = await program.methods
+      .migrateTreeOwner()
+      .accounts({
+        payer: wallet.publicKey,
+        oldAuthority: oldOwner.publicKey,
+        treeConfig: legacyTreeConfig,
+        tree: legacyTree.publicKey,
+      }).signers([wallet.payer, oldOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(migrateTx);
 
-    assert.strictEqual(
-      programStateAccountInfo.data.length,
-      46 + 1 * 66, // space = account disc (8) + pubkey (32) + vec size (4) + tree count (2) + max_#_trees * tree info (66)
-      "tree_state account data size is incorrect"
+    const [legacyTreeOwner] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tree_owner"), legacyTree.publicKey.toBuffer()],
+      program.programId
     );
+    const treeConfigData = await fetchTreeConfigFromSeeds(umi, {
+      merkleTree: legacyTree.publicKey.toBase58() as UmiPK,
+    });
+    assert.strictEqual(treeConfigData.treeDelegate.toString(), legacyTreeOwner.toBase58());
+
+    const mintTx = await program.methods
+      .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 10, [], Buffer.from([]), null, false)
+      .accounts({
+        payer: oldOwner.publicKey,
+        tree: legacyTree.publicKey,
+        treeConfig: legacyTreeConfig,
+        leafOwner: Keypair.generate().publicKey,
+      }).signers([oldOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(mintTx);
+
+    const legacyTreeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, legacyTree.publicKey);
+    assert.strictEqual(legacyTreeAccount.tree.rightMostPath.index, 1);
   });
 
-  it.only("Creates an empty merkle tree", async () => {
+  it("Registers content and mints a knowledge NFT by its hash", async () => {
+    const contentUri = "https://example.com/knowledge/dedup.json";
+    const contentHash = Array.from(nacl.hash(Buffer.from(contentUri)).slice(0, 32));
+    const contentRegistryOf = (authority: PublicKey) => PublicKey.findProgramAddressSync(
+      [Buffer.from("content"), authority.toBuffer(), Buffer.from(contentHash)],
+      program.programId
+    )[0];
+    const contentRegistry = contentRegistryOf(wallet.publicKey);
 
-    const tx = await program.methods
-    .createTree(maxDepthSizePair.maxDepth, maxDepthSizePair.maxBufferSize)
-    // Do not pass accounts that are automatically resolved
-    .accounts({
-      tree: tree.publicKey,
-      treeConfig: tree_config,
-      payer: wallet.publicKey,
-    }).signers([wallet.payer])
-    .rpc({ commitment: 'confirmed'});
+    // Registering a hash first does not claim it for other authorities
+    const squatter = Keypair.generate();
+    await confirmTransaction(await connection.requestAirdrop(squatter.publicKey, LAMPORTS_PER_SOL));
+    const squatTx = await program.methods
+      .registerContent(contentHash, "https://example.com/squatted.json")
+      .accounts({
+        authority: squatter.publicKey,
+      }).signers([squatter])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(squatTx);
 
-    treeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(
-      connection,
-      tree.publicKey
+    const registerTx = await program.methods
+      .registerContent(contentHash, contentUri)
+      .accounts({
+        authority: wallet.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(registerTx);
+
+    const registry = await program.account.contentRegistry.fetch(contentRegistry);
+    assert.strictEqual(registry.uri, contentUri);
+    assert.ok(registry.authority.equals(wallet.publicKey));
+    const squatted = await program.account.contentRegistry.fetch(contentRegistryOf(squatter.publicKey));
+    assert.strictEqual(squatted.uri, "https://example.com/squatted.json");
+
+    const mintByHash = (hash: number[], registryAccount: PublicKey) => program.methods
+      .mintKnowledge(metadata.name, metadata.symbol, "", 10, [], Buffer.from([]), hash, false)
+      .accounts({
+        payer: wallet.publicKey,
+        tree: tree.publicKey,
+        treeConfig: tree_config,
+        leafOwner: Keypair.generate().publicKey,
+        contentRegistry: registryAccount,
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+
+    const mintTx = await mintByHash(contentHash, contentRegistry);
+    await confirmTransaction(mintTx);
+
+    // A registry only resolves the hash it was registered for
+    const unknownHash = Array.from(Keypair.generate().publicKey.toBuffer());
+    try {
+      await mintByHash(unknownHash, contentRegistry);
+      assert.fail("Mint with an unregistered content hash should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "ContentNotRegistered");
+    }
+  });
+
+  it("Reports remaining capacity for a fresh and a near full tree", async () => {
+    const capacityTree = await createNewTree({ maxDepth: 3, maxBufferSize: 8 }, 0);
+    const [capacityTreeRegistry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), capacityTree.tree.publicKey.toBuffer()],
+      program.programId
     );
 
-    console.log('MaxBufferSize', treeAccount.getMaxBufferSize());
-    console.log('MaxDepth', treeAccount.getMaxDepth());
-    console.log('Tree Authority', treeAccount.getAuthority().toString());
-    console.log('Initial Root:', treeAccount.getCurrentRoot().toString('hex'));
+    // Without the registry, the count is read from the tree's rightmost path instead
+    const remainingCapacity = async (treeRegistry: PublicKey | null) => (await program.methods
+      .remainingCapacity()
+      .accounts({
+        tree: capacityTree.tree.publicKey,
+        treeRegistry,
+      })
+      .view()).toNumber();
+
+    assert.strictEqual(await remainingCapacity(capacityTreeRegistry), 8);
+    assert.strictEqual(await remainingCapacity(null), 8);
+
+    const items = Array.from({ length: 7 }, (_, i) => ({
+      name: `${metadata.name}-${i}`,
+      symbol: metadata.symbol,
+      uri: metadata.uri,
+      sellerFeeBasisPoints: 0,
+      creators: [],
+    }));
+    const batchOwner = Keypair.generate();
+    const tx = await program.methods
+      .mintBatch(items, Buffer.from([]))
+      .accounts({
+        payer: wallet.publicKey,
+        treeConfig: capacityTree.treeConfig,
+        tree: capacityTree.tree.publicKey,
+        leafOwner: batchOwner.publicKey,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 })])
+      .signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(tx);
 
-    assert.ok(programStateAccountInfo, "Program state account should be initialized before creating tree"); 
-    assert.strictEqual(treeAccount.getMaxDepth(), maxDepthSizePair.maxDepth, "Unexpected max depth");
-    assert.strictEqual(treeAccount.getMaxBufferSize(), maxDepthSizePair.maxBufferSize, "Unexepected max buffer size");
-    
-    programStateData = await program.account.programState.fetch(programStatePda);
-    assert.strictEqual(
-      programStateData.treeCount,
-      1,
-      "Unexpected number of merkle trees"
+    assert.strictEqual(await remainingCapacity(capacityTreeRegistry), 1);
+    assert.strictEqual(await remainingCapacity(null), 1);
+
+    // Burning a leaf leaves its slot taken, leaves are only appended
+    const [assetId] = findLeafAssetIdPda(umi, {
+      merkleTree: capacityTree.tree.publicKey.toBase58() as UmiPK,
+      leafIndex: 0,
+    });
+    const leaf = await getLeafProof(assetId);
+    const burnTx = await program.methods
+      .burnKnowledge(leaf.root, leaf.dataHash, leaf.creatorHash, leaf.nonce, leaf.index)
+      .accounts({
+        treeConfig: capacityTree.treeConfig,
+        leafOwner: batchOwner.publicKey,
+        leafDelegate: batchOwner.publicKey,
+        tree: capacityTree.tree.publicKey,
+      }).remainingAccounts(leaf.proof).signers([batchOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(burnTx);
+    assert.strictEqual(await remainingCapacity(capacityTreeRegistry), 1);
+  });
+
+  it("Backfills the registry of a tree created without one", async () => {
+    const depthSizePair: ValidDepthSizePair = { maxDepth: 3, maxBufferSize: 8 };
+    const { tree: legacyTree, treeConfig: legacyTreeConfig } = await createNewTree(depthSizePair, 0);
+    const [legacyRegistry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), legacyTree.publicKey.toBuffer()],
+      program.programId
     );
+
+    // Closing the registry leaves the tree as it was before registries existed
+    const closeTx = await program.methods
+      .closeTree(Buffer.from([]))
+      .accounts({
+        creator: wallet.publicKey,
+        treeConfig: legacyTreeConfig,
+        tree: legacyTree.publicKey,
+        destination: wallet.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(closeTx);
+    assert.isNull(await connection.getAccountInfo(legacyRegistry));
+
+    const backfill = () => program.methods
+      .backfillRegistry(depthSizePair.maxDepth, depthSizePair.maxBufferSize)
+      .accounts({
+        creator: wallet.publicKey,
+        treeConfig: legacyTreeConfig,
+        tree: legacyTree.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+
+    await confirmTransaction(await backfill());
+
+    const registry = await program.account.treeRegistry.fetch(legacyRegistry);
+    assert.strictEqual(registry.maxDepth, depthSizePair.maxDepth);
+    assert.strictEqual(registry.maxBufferSize, depthSizePair.maxBufferSize);
+    assert.strictEqual(registry.canopyDepth, 0);
+    assert.strictEqual(registry.numMinted.toNumber(), 0);
+
+    // Backfilled trees are registered and mintable like any other
+    const programState = await program.account.programState.fetch(programStatePda);
+    const entries = programState.trees.filter((info) => info.treeAddress.equals(legacyTree.publicKey));
+    assert.strictEqual(entries.length, 1, "Tree should be registered once");
+    const mintTx = await program.methods
+      .mintKnowledge(metadata.name, metadata.symbol, metadata.uri, 0, [], Buffer.from([]), null, false)
+      .accounts({
+        payer: wallet.publicKey,
+        tree: legacyTree.publicKey,
+        treeConfig: legacyTreeConfig,
+        leafOwner: Keypair.generate().publicKey,
+      }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+    await confirmTransaction(mintTx);
+
+    try {
+      await backfill();
+      assert.fail("Backfilling an existing registry should fail");
+    } catch (err) {
+      assert.isTrue(
+        (err as anchor.web3.SendTransactionError).logs.some((log) => log.includes("already in use")),
+        "Registry init should fail because the account exists"
+      );
+    }
   });
 
-  it("Mints an NFT to an existing merkle tree", async () => {
-    const leafOwner = Keypair.generate();
+  it("Hands the tree delegate to a proposed owner once it accepts", async () => {
+    const { tree: handoffTree, treeConfig: handoffTreeConfig } = await createNewTree({ maxDepth: 3, maxBufferSize: 8 }, 0);
+    const [pendingOwner] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pending_owner"), handoffTree.publicKey.toBuffer()],
+      program.programId
+    );
+    const newOwner = Keypair.generate();
 
-    assert.ok(treeAccount, "Tree account should exist before minting NFTs");
-    
-    const tx: TransactionSignature = await program.methods
-    .mint(metadata.name, metadata.symbol, metadata.uri, 10)
-    .accounts({
-      payer: wallet.publicKey,
-      tree: tree.publicKey,
-      treeAuth: tree_config,
-      leafOwner: leafOwner.publicKey
-    }).signers([wallet.payer]).rpc({ commitment: 'confirmed'});
+    const proposeTx = await program.methods
+      .proposeTreeOwner()
+      .accounts({
+        creator: wallet.publicKey,
+        tree: handoffTree.publicKey,
+        proposed: newOwner.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(proposeTx);
 
-    const transactionDetails = await connection.getTransaction(tx, {
-      maxSupportedTransactionVersion: 1,
-      commitment: 'confirmed'
-    });
+    const pending = await program.account.pendingOwner.fetch(pendingOwner);
+    assert.strictEqual(pending.proposed.toBase58(), newOwner.publicKey.toBase58());
 
-    //Extract leaf index from change logs
-    let changeLogEvents: ChangeLogEventV1[] = [];
-    transactionDetails.meta?.innerInstructions?.forEach((compiledIx) => {
-      compiledIx.instructions.forEach((innerIx) => {
-        try {
-          // try to deserialize the cpi data as a changelog event
-          changeLogEvents.push(
-            deserializeChangeLogEventV1(Buffer.from(bs58.decode(innerIx.data)))
-          );
-        } catch (__) {
-          // this noop cpi is not a changelog event. do nothing with it.
-        }
-      });
+    // Any other key cannot accept the proposal
+    const outsider = Keypair.generate();
+    try {
+      await program.methods
+        .acceptTreeOwner(Buffer.from([]))
+        .accounts({
+          proposed: outsider.publicKey,
+          creator: wallet.publicKey,
+          treeConfig: handoffTreeConfig,
+          tree: handoffTree.publicKey,
+        }).signers([outsider])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Accept by a key other than the proposed one should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "Unauthorized");
+    }
+
+    const acceptTx = await program.methods
+      .acceptTreeOwner(Buffer.from([]))
+      .accounts({
+        proposed: newOwner.publicKey,
+        creator: wallet.publicKey,
+        treeConfig: handoffTreeConfig,
+        tree: handoffTree.publicKey,
+      }).signers([newOwner])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(acceptTx);
+
+    const treeConfigData = await fetchTreeConfigFromSeeds(umi, {
+      merkleTree: handoffTree.publicKey.toBase58() as UmiPK,
     });
-    const leafIndex = changeLogEvents[0].index;
+    assert.strictEqual(treeConfigData.treeDelegate.toString(), newOwner.publicKey.toBase58());
+    assert.isNull(await connection.getAccountInfo(pendingOwner), "Pending owner should be closed on accept");
 
-    const [assetId, bump] = await findLeafAssetIdPda(umi, {
-      merkleTree: tree.publicKey.toBase58() as UmiPK,
-      leafIndex: leafIndex,
+    // The tree_owner pda is still the tree creator, so this program can still mint
+    const authority = await program.methods
+      .getTreeAuthority(Buffer.from([]))
+      .accounts({
+        treeConfig: handoffTreeConfig,
+        tree: handoffTree.publicKey,
+      })
+      .view();
+    assert.isTrue(authority.isTreeOwner);
+  });
+
+  it("Cancels a proposed tree owner before it accepts", async () => {
+    const { tree: handoffTree, treeConfig: handoffTreeConfig } = await createNewTree({ maxDepth: 3, maxBufferSize: 8 }, 0);
+    const [pendingOwner] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pending_owner"), handoffTree.publicKey.toBuffer()],
+      program.programId
+    );
+    const [handoffTreeOwner] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tree_owner"), handoffTree.publicKey.toBuffer()],
+      program.programId
+    );
+    const newOwner = Keypair.generate();
+
+    const proposeTx = await program.methods
+      .proposeTreeOwner()
+      .accounts({
+        creator: wallet.publicKey,
+        tree: handoffTree.publicKey,
+        proposed: newOwner.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(proposeTx);
+
+    const cancelTx = await program.methods
+      .cancelTreeOwner()
+      .accounts({
+        creator: wallet.publicKey,
+        tree: handoffTree.publicKey,
+      }).signers([wallet.payer])
+    .rpc({ commitment: 'confirmed' });
+    await confirmTransaction(cancelTx);
+    assert.isNull(await connection.getAccountInfo(pendingOwner), "Pending owner should be closed on cancel");
+
+    // The cancelled proposal can no longer be accepted
+    try {
+      await program.methods
+        .acceptTreeOwner(Buffer.from([]))
+        .accounts({
+          proposed: newOwner.publicKey,
+          creator: wallet.publicKey,
+          treeConfig: handoffTreeConfig,
+          tree: handoffTree.publicKey,
+        }).signers([newOwner])
+      .rpc({ commitment: 'confirmed' });
+      assert.fail("Accepting a cancelled proposal should fail");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "AccountNotInitialized");
+    }
+
+    const treeConfigData = await fetchTreeConfigFromSeeds(umi, {
+      merkleTree: handoffTree.publicKey.toBase58() as UmiPK,
     });
+    assert.strictEqual(treeConfigData.treeDelegate.toString(), handoffTreeOwner.toBase58());
+  });
 
-    console.log("Leaf Index:", leafIndex);
-    console.log("Tree pk:", tree.publicKey.toBase58());
+  it("Rejects mints with Paused while the program is paused", async () => {
+    const setPaused = async (paused: boolean) => {
+      const tx = await program.methods
+        .setPaused(paused)
+        .accounts({
+          admin: wallet.publicKey,
+        }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+      await confirmTransaction(tx);
+    }
 
-    await confirmTransaction(tx);
-    const rpcAsset = await umi.rpc.getAsset(assetId);
+    await setPaused(true);
+    try {
+      await mintLeaf(Keypair.generate().publicKey);
+      assert.fail("Mint should fail while paused");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "Paused");
+    }
 
-    console.log(`Transaction: https://explorer.solana.com/tx/${tx}?cluster=devnet`);
-    console.log("NFT Name:", rpcAsset.content.metadata.name);
+    // Every other mint path is halted too
+    try {
+      await program.methods
+        .mintBatch([{ name: metadata.name, symbol: metadata.symbol, uri: metadata.uri, sellerFeeBasisPoints: 0, creators: [] }], Buffer.from([]))
+        .accounts({
+          payer: wallet.publicKey,
+          treeConfig: tree_config,
+          tree: tree.publicKey,
+          leafOwner: Keypair.generate().publicKey,
+        }).signers([wallet.payer]).rpc({ commitment: 'confirmed' });
+      assert.fail("Batch mint should fail while paused");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "Paused");
+    }
+
+    // Only the admin may flip the switch
+    const stranger = Keypair.generate();
+    try {
+      await program.methods
+        .setPaused(false)
+        .accounts({
+          admin: stranger.publicKey,
+        }).signers([stranger]).rpc({ commitment: 'confirmed' });
+      assert.fail("Non admin should not unpause");
+    } catch (err) {
+      assert.strictEqual((err as anchor.AnchorError).error.errorCode.code, "Unauthorized");
+    }
 
+    await setPaused(false);
+    const assetId = await mintLeaf(Keypair.generate().publicKey);
+    const asset = await getAssetWithProof(umi, assetId);
+    assert.ok(asset, "Mint should succeed once unpaused");
   });
 
   it("Initializes a new inference task collection", async () => {
@@ -543,7 +3279,7 @@ describe("deinference", () => {
 
   });
 
-  it.only("Aggregates the results for an inference request", async () => {
+  it("Aggregates the results for an inference request", async () => {
     // Create new nft collection
     const task_account = generateSigner(umi);
 
@@ -656,6 +3392,16 @@ describe("deinference", () => {
         signature: signature
       });
 
+      // Each model owner mints its own leaf, so it must be on the tree's minter allowlist
+      const add_minter_tx = await program.methods
+        .addMinter(account.publicKey)
+        .accounts({
+          creator: wallet.publicKey,
+          tree: tree.publicKey,
+        }).signers([wallet.payer])
+      .rpc({ commitment: 'confirmed' });
+      await confirmTransaction(add_minter_tx);
+
       const mint_tx = await program.methods
         .mintToTask(model_metadata.name, model_metadata.symbol, model_metadata.uri, Array.from(signature), 0)
         .accounts({